@@ -12,8 +12,11 @@ dependencies {
     implementation("io.ktor:ktor-client-cio:3.0.0-beta-1")
     implementation("org.jsoup:jsoup:1.15.3")
     implementation("com.google.code.gson:gson:2.9.0")
+    testImplementation(kotlin("test"))
 }
 
+tasks.test { useJUnitPlatform() }
+
 tasks.withType<org.jetbrains.kotlin.gradle.tasks.KotlinCompile> {
     kotlinOptions.jvmTarget = "1.8"
 }
@@ -0,0 +1,194 @@
+import com.google.gson.GsonBuilder
+
+private val gson = GsonBuilder().setPrettyPrinting().disableHtmlEscaping().create()
+
+enum class ChangeKind { Added, Removed, Changed }
+
+enum class SchemaItem { Type, Field, Method, Parameter, Variant }
+
+/**
+ * A difference between two snapshots of the docs.
+ *
+ * @property name e.g. "Message", "Message.date", "sendMessage.chat_id" or "ChatMember.ChatMemberOwner"
+ * @property breaking whether code using the generated Rust models may stop compiling
+ */
+data class SchemaChange(
+    val kind: ChangeKind,
+    val item: SchemaItem,
+    val name: String,
+    val detail: String,
+    val breaking: Boolean
+)
+
+/**
+ * Changes from this snapshot to [new].
+ *
+ * Removals, type changes, required changes, new required fields or parameters and new enum variants (see [rustVariants])
+ * break the Rust models.
+ * So do the changes of the Rust API read from the descriptions (units, targets, `EditResult` responses, boxes)
+ * and lost derives, see [toRustSignatures]. New optional fields and parameters don't, they are set through the builders,
+ * and neither do new or removed deprecations.
+ */
+fun List<DocSection>.diff(new: List<DocSection>): List<SchemaChange> {
+    val oldTypes = flatMap { it.docTypes }.associateBy { it.name }
+    val newTypes = new.flatMap { it.docTypes }.associateBy { it.name }
+    val oldMethods = flatMap { it.docMethods }.associateBy { it.name }
+    val newMethods = new.flatMap { it.docMethods }.associateBy { it.name }
+    val oldRust = toRustSignatures()
+    val newRust = new.toRustSignatures()
+    val changes = mutableListOf<SchemaChange>()
+    (oldTypes.keys + newTypes.keys).forEach { name ->
+        val old = oldTypes[name]
+        val type = newTypes[name]
+        when {
+            old == null -> changes += SchemaChange(ChangeKind.Added, SchemaItem.Type, name, "new type", breaking = false)
+            type == null -> changes += SchemaChange(ChangeKind.Removed, SchemaItem.Type, name, "type removed", breaking = true)
+            else -> {
+                diffDerives(name, oldRust[name], newRust[name])?.let { changes += it }
+                changes += diffMembers(
+                    item = SchemaItem.Field,
+                    owner = name,
+                    old = old.docFields.map { DocParameter(it.name, it.description, it.type, it.required) },
+                    new = type.docFields.map { DocParameter(it.name, it.description, it.type, it.required) },
+                    oldRust = oldRust,
+                    newRust = newRust
+                )
+            }
+        }
+    }
+    (oldMethods.keys + newMethods.keys).forEach { name ->
+        val old = oldMethods[name]
+        val method = newMethods[name]
+        when {
+            old == null -> changes += SchemaChange(ChangeKind.Added, SchemaItem.Method, name, "new method", breaking = false)
+            method == null -> changes += SchemaChange(ChangeKind.Removed, SchemaItem.Method, name, "method removed", breaking = true)
+            else -> {
+                if (old.returns.name != method.returns.name) {
+                    changes += SchemaChange(
+                        ChangeKind.Changed, SchemaItem.Method, name,
+                        "returns ${method.returns} instead of ${old.returns}", breaking = true
+                    )
+                } else if (oldRust[name] != newRust[name]) {
+                    changes += SchemaChange(
+                        ChangeKind.Changed, SchemaItem.Method, name,
+                        "Rust response ${oldRust[name]} → ${newRust[name]}", breaking = true
+                    )
+                }
+                changes += diffMembers(SchemaItem.Parameter, name, old.docParameters, method.docParameters, oldRust, newRust)
+            }
+        }
+    }
+    // the variants of the generated enums, a subtype without the tag of the others isn't one
+    TelegramType.allSuper.filterIsInstance<TelegramType.Super>().forEach { superType ->
+        val oldVariants = superType.rustVariants(oldTypes.values.toList()).map { it.name }.toSet()
+        val newVariants = superType.rustVariants(newTypes.values.toList()).map { it.name }.toSet()
+        (newVariants - oldVariants).forEach { variant ->
+            changes += SchemaChange(
+                ChangeKind.Added, SchemaItem.Variant, "${superType.name}.$variant",
+                "new variant, exhaustive matches must handle it", breaking = true
+            )
+        }
+        (oldVariants - newVariants).forEach { variant ->
+            changes += SchemaChange(ChangeKind.Removed, SchemaItem.Variant, "${superType.name}.$variant", "variant removed", breaking = true)
+        }
+    }
+    val oldDeprecations = toRustDeprecations()
+    val newDeprecations = new.toRustDeprecations()
+    val kept = toItemNames() intersect new.toItemNames()
+    fun itemOf(name: String) = when {
+        '.' !in name -> if (name in newTypes) SchemaItem.Type else SchemaItem.Method
+        name.substringBefore('.') in newTypes -> SchemaItem.Field
+        else -> SchemaItem.Parameter
+    }
+    (newDeprecations.keys - oldDeprecations.keys).filter { it in kept }.forEach { name ->
+        changes += SchemaChange(ChangeKind.Changed, itemOf(name), name, "deprecated: ${newDeprecations[name]}", breaking = false)
+    }
+    (oldDeprecations.keys - newDeprecations.keys).filter { it in kept }.forEach { name ->
+        changes += SchemaChange(ChangeKind.Changed, itemOf(name), name, "no longer deprecated", breaking = false)
+    }
+    return changes
+}
+
+// Example: a type getting its first Float field no longer derives Eq and Hash
+private fun diffDerives(name: String, old: String?, new: String?): SchemaChange? {
+    fun String?.derives() = orEmpty().removePrefix("#[derive(").removeSuffix(")]").split(", ").toSet()
+    val lost = old.derives() - new.derives()
+    val gained = new.derives() - old.derives()
+    if (lost.isEmpty() && gained.isEmpty()) return null
+    val detail = listOfNotNull(
+        lost.takeIf { it.isNotEmpty() }?.let { "no longer derives ${it.joinToString()}" },
+        gained.takeIf { it.isNotEmpty() }?.let { "derives ${it.joinToString()}" },
+    ).joinToString(", ")
+    return SchemaChange(ChangeKind.Changed, SchemaItem.Type, name, detail, breaking = lost.isNotEmpty())
+}
+
+private fun diffMembers(
+    item: SchemaItem,
+    owner: String,
+    old: List<DocParameter>,
+    new: List<DocParameter>,
+    oldRust: Map<String, String>,
+    newRust: Map<String, String>
+) = buildList {
+    val oldByName = old.associateBy { it.name }
+    val newByName = new.associateBy { it.name }
+    (oldByName.keys + newByName.keys).forEach { name ->
+        val before = oldByName[name]
+        val after = newByName[name]
+        val fullName = "$owner.$name"
+        when {
+            before == null -> add(
+                SchemaChange(
+                    ChangeKind.Added, item, fullName,
+                    "new ${if (after!!.required) "required" else "optional"} ${after.type}", breaking = after.required
+                )
+            )
+
+            after == null -> add(SchemaChange(ChangeKind.Removed, item, fullName, "${before.type} removed", breaking = true))
+            else -> {
+                if (before.type.name != after.type.name) {
+                    add(SchemaChange(ChangeKind.Changed, item, fullName, "type ${before.type} → ${after.type}", breaking = true))
+                }
+                if (before.required != after.required) {
+                    val detail = if (after.required) "optional → required" else "required → optional"
+                    add(SchemaChange(ChangeKind.Changed, item, fullName, detail, breaking = true))
+                }
+                // Example: a description now saying "Unix time" turns an Integer into a UnixTime
+                if (before.type.name == after.type.name && before.required == after.required && oldRust[fullName] != newRust[fullName]) {
+                    add(SchemaChange(ChangeKind.Changed, item, fullName, "Rust type ${oldRust[fullName]} → ${newRust[fullName]}", breaking = true))
+                }
+            }
+        }
+    }
+}
+
+fun List<SchemaChange>.toChangelogJson(from: String, to: String): String = gson.toJson(
+    mapOf(
+        "from" to from,
+        "to" to to,
+        "breaking" to count { it.breaking },
+        "changes" to this@toChangelogJson
+    )
+)
+
+fun List<SchemaChange>.toChangelogMarkdown(from: String, to: String) = buildString {
+    val changes = this@toChangelogMarkdown
+    appendLine("# $from → $to")
+    if (changes.isEmpty()) {
+        appendLine()
+        appendLine("No changes.")
+    }
+    listOf(
+        "⚠️ Breaking changes for Rust" to changes.filter { it.breaking },
+        "Added" to changes.filter { !it.breaking && it.kind == ChangeKind.Added },
+        "Removed" to changes.filter { !it.breaking && it.kind == ChangeKind.Removed },
+        "Changed" to changes.filter { !it.breaking && it.kind == ChangeKind.Changed },
+    ).filter { it.second.isNotEmpty() }.forEach { (title, section) ->
+        appendLine()
+        appendLine("## $title")
+        appendLine()
+        section.forEach { change ->
+            appendLine("- ${change.item.name.lowercase()} `${change.name}`: ${change.detail}")
+        }
+    }
+}
@@ -1,6 +1,83 @@
 import com.google.gson.GsonBuilder
+import com.google.gson.JsonArray
+import com.google.gson.JsonElement
+import com.google.gson.JsonObject
+import com.google.gson.JsonParser
 
 private val gson = GsonBuilder().setPrettyPrinting().create()
 
 fun List<DocSection>.toJson(): String = gson.toJson(this@toJson)
 
+/**
+ * Reads back the sections written by [toJson].
+ *
+ * @throws IllegalArgumentException naming the JSON path that could not be read, e.g. `$[3].docTypes[2].docFields[0].type`
+ */
+fun String.toDocSections(): List<DocSection> = at("$") {
+    JsonParser.parseString(this@toDocSections).asJsonArray.mapAt { section ->
+        DocSection(
+            name = section.string("name"),
+            description = section.string("description"),
+            docTypes = section.array("docTypes").mapAt { type ->
+                DocType(
+                    name = type.string("name"),
+                    description = type.string("description"),
+                    docFields = type.array("docFields").mapAt { field ->
+                        DocField(
+                            name = field.string("name"),
+                            description = field.string("description"),
+                            type = field.telegramType("type"),
+                            required = field.boolean("required")
+                        )
+                    }
+                )
+            },
+            docMethods = section.array("docMethods").mapAt { method ->
+                DocMethod(
+                    name = method.string("name"),
+                    description = method.string("description"),
+                    docParameters = method.array("docParameters").mapAt { parameter ->
+                        DocParameter(
+                            name = parameter.string("name"),
+                            description = parameter.string("description"),
+                            type = parameter.telegramType("type"),
+                            required = parameter.boolean("required")
+                        )
+                    },
+                    returns = method.telegramType("returns")
+                )
+            }
+        )
+    }
+}
+
+private class JsonPathException(val path: String, val reason: String, cause: Throwable?) :
+    IllegalArgumentException("$path: $reason", cause)
+
+private fun <T> at(path: String, block: () -> T): T = try {
+    block()
+} catch (e: JsonPathException) {
+    throw JsonPathException(path + e.path, e.reason, e.cause)
+} catch (e: RuntimeException) {
+    throw JsonPathException(path, e.message ?: e.toString(), e)
+}
+
+private fun <T> JsonArray.mapAt(transform: (JsonObject) -> T) = mapIndexed { index, element ->
+    at("[$index]") { transform(element.asJsonObject) }
+}
+
+private fun JsonObject.member(name: String): JsonElement = get(name)?.takeUnless { it.isJsonNull } ?: error("missing")
+
+private fun JsonObject.string(name: String): String = at(".$name") { member(name).asString }
+
+private fun JsonObject.boolean(name: String): Boolean = at(".$name") { member(name).asBoolean }
+
+private fun JsonObject.array(name: String): JsonArray = at(".$name") { member(name).asJsonArray }
+
+private fun JsonObject.telegramType(name: String): TelegramType = at(".$name") { member(name).asJsonObject.toTelegramType() }
+
+// Example: {"name": "List<PhotoSize>", "elementType": {"name": "PhotoSize"}}
+private fun JsonObject.toTelegramType(): TelegramType =
+    get("elementType")?.let { at(".elementType") { it.asJsonObject.toTelegramType() } }
+        ?.let { TelegramType.ListType(it) }
+        ?: TelegramType.from(string("name"))
@@ -1,28 +1,673 @@
-fun List<DocSection>.toRustModels() = buildString {
+import com.google.gson.GsonBuilder
+import com.google.gson.JsonArray
+import com.google.gson.JsonElement
+import com.google.gson.JsonObject
+import com.google.gson.JsonPrimitive
+
+/**
+ * @param extraDerives derives added to every model and to the types of their fields (`Until`, `Meters`, ...), e.g. `schemars::JsonSchema`
+ * @param version Bot API version exported as `BOT_API_VERSION`, e.g. "7.8"
+ * @param since Bot API version introducing each item, see [toSince]
+ * @param roundTripTests declares the `round_trip` test module written by [toRustRoundTripTests] in `tests/round_trip.rs`
+ */
+fun List<DocSection>.toRustModels(
+    extraDerives: List<String> = emptyList(),
+    version: String? = null,
+    since: Map<String, String> = emptyMap(),
+    roundTripTests: Boolean = false
+) = buildString {
+    val sections = this@toRustModels.map { section -> section.withRustUnits() }
+    val allType = sections.flatMap { section -> section.docTypes }
+    val typesWithFloat = sections.findRustTypesWithFloat()
+    val inputTypes = sections.findRustInputTypes()
+    val targets = sections.findRustTargets()
+    val boxed = sections.findRustBoxed()
+    fun DocMethod.withRustTargets() = targets.firstOrNull { this in it.methods }?.let { withRustTarget(it) } ?: this
+    val extra = extraDerives.joinToString("") { ", $it" }
+    val deprecated = sections.toRustDeprecations().keys
+    if (version != null) {
+        appendLine("/// Bot API version the models were generated from.")
+        appendLine("pub const BOT_API_VERSION: &str = \"$version\";")
+        appendLine()
+    }
+    TelegramType.allSuper.filterIsInstance<TelegramType.Super>().forEach { type ->
+        val variants = type.rustVariants(allType)
+        if (variants.isNotEmpty()) {
+            val derives = rustDerives(
+                deserialize = false,
+                hashable = type.name !in typesWithFloat,
+                default = false,
+                extraDerives = extraDerives
+            )
+            val allowDeprecated = variants.any { it.name in deprecated }
+            appendLine(type.toRustEnum(variants, derives, boxed, allowDeprecated))
+            appendLine()
+            if (type.name in inputTypes) {
+                appendLine(type.toRustValidate(variants, allowDeprecated))
+                appendLine()
+            }
+            appendLine(type.toRustArbitrary(variants, allowDeprecated))
+            appendLine()
+        }
+    }
+    appendLine(comment("--- Utility ---"))
+    appendLine("/// Deserializes a single JSON value, prefixing errors with the path inside it when `strict` is enabled.")
+    appendLine("fn from_json_value<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> Result<T, serde_json::Error> {")
+    appendLine("    #[cfg(feature = \"strict\")]")
+    appendLine("    return serde_path_to_error::deserialize(value)")
+    appendLine("        .map_err(|e| serde::de::Error::custom(format_args!(\"{}: {}\", e.path(), e.inner())));")
+    appendLine("    #[cfg(not(feature = \"strict\"))]")
+    appendLine("    return serde_json::from_value(value);")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Deserializes `json`, rejecting unknown fields and tags. The error names the JSON path that could not be mapped.")
+    appendLine("#[cfg(feature = \"strict\")]")
+    appendLine("pub fn from_str_strict<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, serde_path_to_error::Error<serde_json::Error>> {")
+    appendLine("    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(json))")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Envelope of every Bot API response, `result` is present when `ok` is true.")
+    appendLine("#[derive(Serialize, Deserialize, Clone, PartialEq, Debug$extra)]")
+    appendLine("pub struct TelegramResponse<T> {")
+    appendLine("    pub ok: bool,")
+    appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+    appendLine("    pub result: Option<T>,")
+    appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+    appendLine("    pub description: Option<String>,")
+    appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+    appendLine("    pub error_code: Option<Integer>,")
+    appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+    appendLine("    pub parameters: Option<ResponseParameters>,")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A Bot API method: the request is serialized as parameters of [TelegramMethod::NAME].")
+    appendLine("pub trait TelegramMethod: Serialize {")
+    appendLine("    /// Type of `result` in the [TelegramResponse].")
+    appendLine("    type Response: serde::de::DeserializeOwned + Send;")
+    appendLine("    /// Name of the method, e.g. `sendMessage`.")
+    appendLine("    const NAME: &'static str;")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Sends requests to the Bot API, implemented by the crate including the models over its HTTP client.")
+    appendLine("pub trait TelegramClient {")
+    appendLine("    /// Error of a call, e.g. a transport error or a [TelegramResponse] with `ok` false.")
+    appendLine("    type Error;")
+    appendLine()
+    appendLine("    /// Calls `method`, returning the `result` of its response.")
+    appendLine("    fn call<M: TelegramMethod + Sync>(&self, method: &M) -> impl std::future::Future<Output = Result<M::Response, Self::Error>> + Send;")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A documented limit not respected by a field, see [Validate::validate].")
+    appendLine("#[derive(Clone, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct Violation {")
+    appendLine("    /// Path of the field, e.g. `results[2].caption`.")
+    appendLine("    pub field: String,")
+    appendLine("    /// What is wrong, e.g. `must be 0-1024 characters, got 1100`.")
+    appendLine("    pub message: String,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::fmt::Display for Violation {")
+    appendLine("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")
+    appendLine("        write!(f, \"{} {}\", self.field, self.message)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Documented limits of requests and input types, checked locally before calling the Bot API.")
+    appendLine("pub trait Validate {")
+    appendLine("    /// Appends the violations of `self` to `violations`, `path` is the path of `self` (empty for the root).")
+    appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>);")
+    appendLine()
+    appendLine("    /// Checks the documented limits, returning every violation found.")
+    appendLine("    fn validate(&self) -> Result<(), Vec<Violation>> {")
+    appendLine("        let mut violations = Vec::new();")
+    appendLine("        self.violations(\"\", &mut violations);")
+    appendLine("        if violations.is_empty() { Ok(()) } else { Err(violations) }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<T: Validate> Validate for Option<T> {")
+    appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>) {")
+    appendLine("        if let Some(value) = self {")
+    appendLine("            value.violations(path, violations);")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<T: Validate> Validate for Vec<T> {")
+    appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>) {")
+    appendLine("        for (index, value) in self.iter().enumerate() {")
+    appendLine("            value.violations(&format!(\"{}[{}]\", path, index), violations);")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<T: Validate> Validate for Box<T> {")
+    appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>) {")
+    appendLine("        self.as_ref().violations(path, violations);")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn field_path(path: &str, field: &str) -> String {")
+    appendLine("    if path.is_empty() { field.to_string() } else { format!(\"{}.{}\", path, field) }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn check_range<T: PartialOrd + std::fmt::Display>(violations: &mut Vec<Violation>, field: String, value: T, min: T, max: T) {")
+    appendLine("    if value < min || value > max {")
+    appendLine("        violations.push(Violation { field, message: format!(\"must be {}-{}, got {}\", min, max, value) });")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn check_length(violations: &mut Vec<Violation>, field: String, length: usize, min: usize, max: usize, unit: &str) {")
+    appendLine("    if length < min || length > max {")
+    appendLine("        violations.push(Violation { field, message: format!(\"must be {}-{} {}, got {}\", min, max, unit, length) });")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A string of `min..=max` characters, measured by `len` (`char::len_utf16` or `char::len_utf8`).")
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    appendLine("fn arbitrary_string(u: &mut arbitrary::Unstructured, min: usize, max: usize, len: fn(char) -> usize) -> arbitrary::Result<String> {")
+    appendLine("    let length = u.int_in_range(min..=max)?;")
+    appendLine("    let mut value = String::new();")
+    appendLine("    let mut count = 0;")
+    appendLine("    while count < length {")
+    appendLine("        let c: char = u.arbitrary()?;")
+    appendLine("        let c = if count + len(c) > length { 'a' } else { c };")
+    appendLine("        count += len(c);")
+    appendLine("        value.push(c);")
+    appendLine("    }")
+    appendLine("    Ok(value)")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A list of `min..=max` items.")
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    appendLine("fn arbitrary_vec<'a, T: arbitrary::Arbitrary<'a>>(u: &mut arbitrary::Unstructured<'a>, min: usize, max: usize) -> arbitrary::Result<Vec<T>> {")
+    appendLine("    let length = u.int_in_range(min..=max)?;")
+    appendLine("    (0..length).map(|_| u.arbitrary()).collect()")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A number in `min..=max` with at most 6 decimals: JSON has no NaN nor infinity, and longer numbers may change in a round trip.")
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    appendLine("fn arbitrary_float(u: &mut arbitrary::Unstructured, min: f64, max: f64) -> arbitrary::Result<f64> {")
+    appendLine("    let micros = u.int_in_range((min * 1e6) as i64..=(max * 1e6) as i64)?;")
+    appendLine("    Ok(micros as f64 / 1e6)")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A point in time, sent as a Unix timestamp: a UTC date with the `chrono` or `time` feature, the seconds otherwise.")
+    appendLine("#[cfg(feature = \"chrono\")]")
+    appendLine("pub type UnixTime = chrono::DateTime<chrono::Utc>;")
+    appendLine("/// A point in time, sent as a Unix timestamp: a UTC date with the `chrono` or `time` feature, the seconds otherwise.")
+    appendLine("#[cfg(all(feature = \"time\", not(feature = \"chrono\")))]")
+    appendLine("pub type UnixTime = time::OffsetDateTime;")
+    appendLine("/// A point in time, sent as a Unix timestamp: a UTC date with the `chrono` or `time` feature, the seconds otherwise.")
+    appendLine("#[cfg(not(any(feature = \"chrono\", feature = \"time\")))]")
+    appendLine("pub type UnixTime = Integer;")
+    appendLine()
+    appendLine("/// Serde adapter of [UnixTime] fields, used with `serialize_with` and `deserialize_with`.")
+    appendLine("pub mod unix_time {")
+    appendLine("    use super::*;")
+    appendLine()
+    appendLine("    #[cfg(feature = \"chrono\")]")
+    appendLine("    fn from_timestamp(seconds: i64) -> Option<UnixTime> {")
+    appendLine("        chrono::DateTime::from_timestamp(seconds, 0)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(all(feature = \"time\", not(feature = \"chrono\")))]")
+    appendLine("    fn from_timestamp(seconds: i64) -> Option<UnixTime> {")
+    appendLine("        time::OffsetDateTime::from_unix_timestamp(seconds).ok()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(feature = \"chrono\")]")
+    appendLine("    fn timestamp(time: &UnixTime) -> i64 {")
+    appendLine("        time.timestamp()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(all(feature = \"time\", not(feature = \"chrono\")))]")
+    appendLine("    fn timestamp(time: &UnixTime) -> i64 {")
+    appendLine("        time.unix_timestamp()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Seconds from `from` to `to`, negative if `to` is before `from`.")
+    appendLine("    #[cfg(any(feature = \"chrono\", feature = \"time\"))]")
+    appendLine("    pub fn seconds_between(from: &UnixTime, to: &UnixTime) -> i64 {")
+    appendLine("        timestamp(to) - timestamp(from)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Seconds from `from` to `to`, negative if `to` is before `from`.")
+    appendLine("    #[cfg(not(any(feature = \"chrono\", feature = \"time\")))]")
+    appendLine("    pub fn seconds_between(from: &UnixTime, to: &UnixTime) -> Integer {")
+    appendLine("        *to - *from")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(any(feature = \"chrono\", feature = \"time\"))]")
+    appendLine("    pub fn serialize<S: serde::Serializer>(time: &UnixTime, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("        serializer.serialize_i64(timestamp(time))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(not(any(feature = \"chrono\", feature = \"time\")))]")
+    appendLine("    pub fn serialize<S: serde::Serializer>(time: &UnixTime, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("        time.serialize(serializer)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(any(feature = \"chrono\", feature = \"time\"))]")
+    appendLine("    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<UnixTime, D::Error> {")
+    appendLine("        let seconds = i64::deserialize(deserializer)?;")
+    appendLine("        from_timestamp(seconds).ok_or_else(|| serde::de::Error::custom(format_args!(\"Unix time out of range: {}\", seconds)))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[cfg(not(any(feature = \"chrono\", feature = \"time\")))]")
+    appendLine("    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<UnixTime, D::Error> {")
+    appendLine("        UnixTime::deserialize(deserializer)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// A date between 1970-01-01T00:00:01Z and 2100-01-01T00:00:00Z, 0 is not a valid date.")
+    appendLine("    #[cfg(all(feature = \"arbitrary\", any(feature = \"chrono\", feature = \"time\")))]")
+    appendLine("    pub fn arbitrary(u: &mut arbitrary::Unstructured) -> arbitrary::Result<UnixTime> {")
+    appendLine("        let seconds = u.int_in_range(1..=4_102_444_800)?;")
+    appendLine("        Ok(from_timestamp(seconds).expect(\"date before 2100\"))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// A date between 1970-01-01T00:00:01Z and 2100-01-01T00:00:00Z, 0 is not a valid date.")
+    appendLine("    #[cfg(all(feature = \"arbitrary\", not(any(feature = \"chrono\", feature = \"time\"))))]")
+    appendLine("    pub fn arbitrary(u: &mut arbitrary::Unstructured) -> arbitrary::Result<UnixTime> {")
+    appendLine("        u.int_in_range(1..=4_102_444_800)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Serde adapter of `Option<UnixTime>` fields, used with `serialize_with`, `deserialize_with` and `default`.")
+    appendLine("    pub mod option {")
+    appendLine("        use super::*;")
+    appendLine()
+    appendLine("        pub fn serialize<S: serde::Serializer>(time: &Option<UnixTime>, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("            match time {")
+    appendLine("                Some(time) => super::serialize(time, serializer),")
+    appendLine("                None => serializer.serialize_none(),")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine()
+    appendLine("        pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<UnixTime>, D::Error> {")
+    appendLine("            Option::<serde_json::Value>::deserialize(deserializer)?")
+    appendLine("                .map(|value| super::deserialize(value).map_err(serde::de::Error::custom))")
+    appendLine("                .transpose()")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * End of a restriction, Telegram sends 0 for [Until::Forever].")
+    appendLine(" *")
+    appendLine(" * In requests, dates less than 30 seconds or more than 366 days from the current time also mean forever, see [Until::is_forever].")
+    appendLine(" * */")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug$extra)]")
+    appendLine("pub enum Until {")
+    appendLine("    /// The restriction never ends, sent as 0.")
+    appendLine("    Forever,")
+    appendLine("    /// The restriction ends at this date.")
+    appendLine("    Date(UnixTime),")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Until {")
+    appendLine("    /// Whether Telegram applies the restriction forever when it is requested at `now`.")
+    appendLine("    pub fn is_forever(&self, now: &UnixTime) -> bool {")
+    appendLine("        match self {")
+    appendLine("            Self::Forever => true,")
+    appendLine("            Self::Date(date) => !(30..=366 * 24 * 60 * 60).contains(&unix_time::seconds_between(now, date)),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<UnixTime> for Until {")
+    appendLine("    fn from(date: UnixTime) -> Self {")
+    appendLine("        Self::Date(date)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Serialize for Until {")
+    appendLine("    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("        match self {")
+    appendLine("            Self::Forever => serializer.serialize_i64(0),")
+    appendLine("            Self::Date(date) => unix_time::serialize(date, serializer),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<'de> Deserialize<'de> for Until {")
+    appendLine("    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {")
+    appendLine("        match serde_json::Value::deserialize(deserializer)? {")
+    appendLine("            value if value.as_i64() == Some(0) => Ok(Self::Forever),")
+    appendLine("            value => unix_time::deserialize(value).map(Self::Date).map_err(serde::de::Error::custom),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    appendLine("impl<'a> arbitrary::Arbitrary<'a> for Until {")
+    appendLine("    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {")
+    appendLine("        Ok(if u.arbitrary()? { Self::Forever } else { Self::Date(unix_time::arbitrary(u)?) })")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A distance in meters.")
+    appendLine("#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Default, Debug$extra)]")
+    appendLine("#[serde(transparent)]")
+    appendLine("pub struct Meters<T>(pub T);")
+    appendLine()
+    appendLine("/// An angle in degrees.")
+    appendLine("#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Default, Debug$extra)]")
+    appendLine("#[serde(transparent)]")
+    appendLine("pub struct Degrees<T>(pub T);")
+    appendLine()
+    appendLine("/// Serde adapter of [std::time::Duration] fields sent as seconds, used with `serialize_with` and `deserialize_with`.")
+    appendLine("pub mod seconds {")
+    appendLine("    use super::*;")
+    appendLine()
+    appendLine("    pub fn serialize<S: serde::Serializer>(duration: &std::time::Duration, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("        serializer.serialize_u64(duration.as_secs())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<std::time::Duration, D::Error> {")
+    appendLine("        u64::deserialize(deserializer).map(std::time::Duration::from_secs)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Serde adapter of `Option<Duration>` fields, used with `serialize_with`, `deserialize_with` and `default`.")
+    appendLine("    pub mod option {")
+    appendLine("        use super::*;")
+    appendLine()
+    appendLine("        pub fn serialize<S: serde::Serializer>(duration: &Option<std::time::Duration>, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("            match duration {")
+    appendLine("                Some(duration) => super::serialize(duration, serializer),")
+    appendLine("                None => serializer.serialize_none(),")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine()
+    appendLine("        pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<std::time::Duration>, D::Error> {")
+    appendLine("            Option::<u64>::deserialize(deserializer).map(|seconds| seconds.map(std::time::Duration::from_secs))")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Period during which a live location can be updated.")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug$extra)]")
+    appendLine("pub enum LivePeriod {")
+    appendLine("    /// The location can be updated during this period.")
+    appendLine("    For(std::time::Duration),")
+    appendLine("    /// The location can be updated indefinitely, sent as 0x7FFFFFFF.")
+    appendLine("    Indefinitely,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl LivePeriod {")
+    appendLine("    /// Value of [LivePeriod::Indefinitely] in JSON.")
+    appendLine("    pub const INDEFINITELY_SECONDS: u64 = 0x7FFF_FFFF;")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<std::time::Duration> for LivePeriod {")
+    appendLine("    fn from(period: std::time::Duration) -> Self {")
+    appendLine("        Self::For(period)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Serialize for LivePeriod {")
+    appendLine("    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {")
+    appendLine("        match self {")
+    appendLine("            Self::For(period) => serializer.serialize_u64(period.as_secs()),")
+    appendLine("            Self::Indefinitely => serializer.serialize_u64(Self::INDEFINITELY_SECONDS),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<'de> Deserialize<'de> for LivePeriod {")
+    appendLine("    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {")
+    appendLine("        match u64::deserialize(deserializer)? {")
+    appendLine("            Self::INDEFINITELY_SECONDS => Ok(Self::Indefinitely),")
+    appendLine("            seconds => Ok(Self::For(std::time::Duration::from_secs(seconds))),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    appendLine("impl<'a> arbitrary::Arbitrary<'a> for LivePeriod {")
+    appendLine("    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {")
+    appendLine("        Ok(if u.arbitrary()? { Self::Indefinitely } else { Self::For(std::time::Duration::from_secs(u.int_in_range(60..=86400)?)) })")
+    appendLine("    }")
+    appendLine("}")
+    if (sections.any { section -> section.docMethods.any { it.rustReturnsEditResult() } }) {
+        appendLine()
+        appendLine("/// Result of editing a message: the edited [Message] for chat messages, `true` for inline messages.")
+        appendLine("#[derive(Clone, PartialEq, Debug$extra)]")
+        appendLine("pub enum EditResult {")
+        appendLine("    Message(Box<Message>),")
+        appendLine("    Inline,")
+        appendLine("}")
+        appendLine()
+        appendLine("impl EditResult {")
+        appendLine("    /// The edited message, `None` if the target was an inline message.")
+        appendLine("    pub fn message(self) -> Option<Message> {")
+        appendLine("        match self {")
+        appendLine("            Self::Message(message) => Some(*message),")
+        appendLine("            Self::Inline => None,")
+        appendLine("        }")
+        appendLine("    }")
+        appendLine("}")
+        appendLine()
+        appendLine("impl Serialize for EditResult {")
+        appendLine("    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {")
+        appendLine("        match self {")
+        appendLine("            Self::Message(message) => message.serialize(serializer),")
+        appendLine("            Self::Inline => serializer.serialize_bool(true),")
+        appendLine("        }")
+        appendLine("    }")
+        appendLine("}")
+        appendLine()
+        appendLine("impl<'de> Deserialize<'de> for EditResult {")
+        appendLine("    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {")
+        appendLine("        match serde_json::Value::deserialize(deserializer)? {")
+        appendLine("            serde_json::Value::Bool(true) => Ok(Self::Inline),")
+        appendLine("            value => from_json_value(value).map(Self::Message).map_err(serde::de::Error::custom),")
+        appendLine("        }")
+        appendLine("    }")
+        appendLine("}")
+    }
     appendLine(comment("--- Parameters & Responses ---"))
-    this@toRustModels.forEach { section ->
+    sections.forEach { section ->
         if (section.docTypes.isNotEmpty()) {
             appendLine(comment(section.name))
             section.docTypes.forEach { type ->
-                appendLine(type.toRustDoc())
-                appendLine(type.toRustDataClass())
+                val derives = rustDerives(
+                    deserialize = true,
+                    hashable = type.name !in typesWithFloat,
+                    default = type.name in inputTypes && type.docFields.none { it.required },
+                    extraDerives = extraDerives
+                )
+                val allowDeprecated = type.rustUses().any { it in deprecated }
+                appendLine(type.toRustDoc(since))
+                appendLine(type.toRustDataClass(derives, boxed, since, allowDeprecated))
+                if (type.name in inputTypes) {
+                    appendLine()
+                    appendLine(type.docFields.toRustBuilder(type.name, type.rustTag(), boxed, allowDeprecated))
+                    appendLine()
+                    type.toRustExclusiveConstructors(allowDeprecated)?.let {
+                        appendLine(it)
+                        appendLine()
+                    }
+                    appendLine(type.docFields.toRustValidate(type.name, inputTypes, allowDeprecated))
+                }
+                appendLine()
+                appendLine(type.toRustArbitrary(allowDeprecated))
                 appendLine()
             }
         }
     }
     appendLine(comment("--- Requests ---"))
-    this@toRustModels.forEach { section ->
+    targets.forEach { target ->
+        val derives = rustDerives(
+            deserialize = true,
+            hashable = target.groups.flatten().none { it.type.hasRustFloat(typesWithFloat) },
+            default = false,
+            extraDerives = extraDerives
+        )
+        appendLine(target.toRustEnum(derives))
+        appendLine()
+    }
+    sections.forEach { section ->
         if (section.docMethods.isNotEmpty()) {
             appendLine(comment(section.name))
-            section.docMethods.forEach { method ->
-                if (method.docParameters.isNotEmpty()) {
-                    appendLine(method.toRustDoc(showReturn = false))
-                    appendLine(method.toRustDataClass())
-                    appendLine()
-                }
+            // methods without parameters get an empty struct too, e.g. GetMeRequest, sent as `{}`
+            section.docMethods.map { it.withRustTargets() }.forEach { method ->
+                val allowDeprecated = method.rustUses().any { it in deprecated }
+                val derives = rustDerives(
+                    deserialize = true,
+                    hashable = method.docParameters.none { it.type.hasRustFloat(typesWithFloat) },
+                    default = method.docParameters.none { it.required },
+                    extraDerives = extraDerives
+                )
+                appendLine(method.toRustDoc(since, showReturn = false))
+                appendLine(method.toRustDataClass(derives, since, targets.map { it.name }.toSet(), allowDeprecated))
+                appendLine()
+                appendLine(method.docParameters.map { DocField(it.name, it.description, it.type, it.required) }
+                    .toRustBuilder("${method.name.capitalize()}Request", tag = null, allowDeprecated = allowDeprecated))
+                appendLine()
+                appendLine(method.toRustMethodImpl(allowDeprecated))
+                appendLine()
+                appendLine(method.docParameters.map { DocField(it.name, it.description, it.type, it.required) }
+                    .toRustValidate("${method.name.capitalize()}Request", inputTypes, allowDeprecated))
+                appendLine()
             }
         }
     }
+    appendLine(comment("--- Text ---"))
+    appendLine(sections.toRustText(inputTypes))
+    appendLine(comment("--- Commands ---"))
+    appendLine(sections.toRustCommands())
+    appendLine(comment("--- Keyboards ---"))
+    appendLine(sections.toRustKeyboards())
+    appendLine(comment("--- Callback data ---"))
+    appendLine(sections.toRustCallbackData())
+    appendLine(comment("--- Dispatch ---"))
+    appendLine(sections.toRustDispatch(boxed))
+    appendLine(comment("--- Dialogue ---"))
+    appendLine(sections.toRustDialogue())
+    appendLine(comment("--- Strategies ---"))
+    appendLine("/// Proptest strategies generating the models through their [arbitrary::Arbitrary] implementation.")
+    appendLine("#[cfg(feature = \"proptest\")]")
+    appendLine("pub mod strategies {")
+    appendLine("    use super::*;")
+    appendLine("    use proptest::prelude::*;")
+    appendLine()
+    appendLine("    /// Strategy for any `T`, shrinking the bytes it is generated from.")
+    appendLine("    pub fn from_arbitrary<T: for<'a> arbitrary::Arbitrary<'a> + std::fmt::Debug + 'static>() -> BoxedStrategy<T> {")
+    appendLine("        proptest::collection::vec(any::<u8>(), 0..4096)")
+    appendLine("            .prop_filter_map(\"not enough data\", |bytes| T::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).ok())")
+    appendLine("            .boxed()")
+    appendLine("    }")
+    val modelNames = TelegramType.allSuper.filterIsInstance<TelegramType.Super>()
+        .filter { type -> type.rustVariants(allType).isNotEmpty() }
+        .map { it.name } + allType.map { it.name }
+    modelNames.distinct().forEach { name ->
+        appendLine()
+        appendLine("    /// Strategy for [$name].")
+        if (name in deprecated) appendLine("    $rustAllowDeprecated")
+        appendLine("    pub fn ${name.replaceFirstChar { it.lowercase() }.toRustSnakeCase()}() -> BoxedStrategy<$name> {")
+        appendLine("        from_arbitrary()")
+        appendLine("    }")
+    }
+    appendLine("}")
+    appendLine(comment("--- Tests ---"))
+    appendLine("#[cfg(test)]")
+    appendLine("mod tests {")
+    appendLine("    use super::*;")
+    appendLine()
+    appendLine("    /// Deserializes the parameters in `json`, checking that serializing the request gives back only these keys.")
+    appendLine("    fn assert_set_keys_only<T: Serialize + serde::de::DeserializeOwned>(json: &str) {")
+    appendLine("        let parameters: serde_json::Value = serde_json::from_str(json).unwrap();")
+    appendLine("        let request: T = serde_json::from_str(json).unwrap();")
+    appendLine("        assert_eq!(serde_json::to_value(&request).unwrap(), parameters);")
+    appendLine("    }")
+    val fixtures = RustFixtures(this@toRustModels)
+    this@toRustModels.flatMap { section -> section.docMethods }.forEach { method ->
+        // requests with a required InputFile have no JSON form
+        val minimal = fixtures.request(method, nested = false) ?: return@forEach
+        appendLine()
+        appendLine("    #[test]")
+        if (method.rustUses().any { it in deprecated }) appendLine("    $rustAllowDeprecated")
+        appendLine("    fn ${method.name.toRustSnakeCase()}_request_skips_unset_parameters() {")
+        appendLine("        assert_set_keys_only::<${method.name.capitalize()}Request>(r#\"$minimal\"#);")
+        fixtures.request(method, nested = true)?.let { nested ->
+            appendLine("        assert_set_keys_only::<${method.name.capitalize()}Request>(r#\"$nested\"#);")
+        }
+        appendLine("    }")
+    }
+    appendLine("}")
+    if (roundTripTests) {
+        appendLine()
+        appendLine("/// Serde round trip of every model against the JSON fixtures in `tests/fixtures`.")
+        appendLine("#[cfg(test)]")
+        appendLine("#[path = \"tests/round_trip.rs\"]")
+        appendLine("mod round_trip;")
+    }
+}
+
+/**
+ * JSON fixtures of every type, synthesized from the field types: `<Type>.minimal` has only the required fields,
+ * `<Type>.full` has every field. The keys are the file names without the `.json` extension.
+ */
+fun List<DocSection>.toRustFixtures(): Map<String, String> {
+    val fixtures = RustFixtures(this)
+    return flatMap { section -> section.docTypes }.flatMap { type ->
+        listOfNotNull(
+            fixtures.minimal(type)?.let { "${type.name}.minimal" to it },
+            fixtures.full(type)?.let { "${type.name}.full" to it },
+        )
+    }.associate { (name, json) -> name to rustGson.toJson(json) + "\n" }
+}
+
+/**
+ * Tests deserializing, serializing and deserializing again each of the [toRustFixtures],
+ * and each variant of the `Super` enums from the fixtures of its type.
+ */
+fun List<DocSection>.toRustRoundTripTests() = buildString {
+    val allType = this@toRustRoundTripTests.flatMap { section -> section.docTypes }
+    val fixtures = this@toRustRoundTripTests.toRustFixtures().keys
+    val deprecated = this@toRustRoundTripTests.toRustDeprecations().keys
+    fun fixturesOf(type: DocType) = listOf("minimal", "full").map { "${type.name}.$it" }.filter { it in fixtures }
+    appendLine("//! Serde round trip of the models, generated together with the JSON files in `fixtures`.")
+    appendLine()
+    appendLine("use super::*;")
+    appendLine()
+    appendLine("/// Deserializes `json`, checking that nothing is lost serializing it back and deserializing it again.")
+    appendLine("fn assert_round_trip<T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug>(json: &str) -> T {")
+    appendLine("    let fixture: serde_json::Value = serde_json::from_str(json).unwrap();")
+    appendLine("    let value: T = serde_json::from_str(json).unwrap();")
+    appendLine("    let serialized = serde_json::to_value(&value).unwrap();")
+    appendLine("    assert_eq!(serialized, fixture);")
+    appendLine("    let deserialized: T = serde_json::from_value(serialized).unwrap();")
+    appendLine("    assert_eq!(deserialized, value);")
+    appendLine("    value")
+    append("}")
+    allType.forEach { type ->
+        fixturesOf(type).forEach { fixture ->
+            appendLine()
+            appendLine()
+            appendLine("#[test]")
+            if (type.name in deprecated) appendLine(rustAllowDeprecated)
+            appendLine("fn ${fixture.replace('.', '_').replaceFirstChar { it.lowercase() }.toRustSnakeCase()}() {")
+            appendLine("    assert_round_trip::<${type.name}>(include_str!(\"fixtures/$fixture.json\"));")
+            append("}")
+        }
+    }
+    TelegramType.allSuper.filterIsInstance<TelegramType.Super>().forEach { superType ->
+        superType.rustVariants(allType).filter { fixturesOf(it).isNotEmpty() }.forEach { type ->
+            val variant = "${superType.name}::${superType.toRustVariantName(type)}"
+            appendLine()
+            appendLine()
+            appendLine("#[test]")
+            if (type.name in deprecated) appendLine(rustAllowDeprecated)
+            appendLine("fn ${type.name.replaceFirstChar { it.lowercase() }.toRustSnakeCase()}_as_${superType.name.replaceFirstChar { it.lowercase() }.toRustSnakeCase()}() {")
+            fixturesOf(type).forEach { fixture ->
+                appendLine("    let value: ${superType.name} = assert_round_trip(include_str!(\"fixtures/$fixture.json\"));")
+                appendLine("    assert!(matches!(value, $variant(_)), \"{:?}\", value);")
+            }
+            append("}")
+        }
+    }
+    appendLine()
 }
 
 private fun comment(text: String) = buildString {
@@ -30,10 +675,192 @@ private fun comment(text: String) = buildString {
     appendLine("/// $text")
 }
 
-private fun DocType.toRustDoc() = buildString {
+// Eq and Hash are sound only without floats, Default only when every field is optional
+private fun rustDerives(deserialize: Boolean, hashable: Boolean, default: Boolean, extraDerives: List<String>) =
+    listOfNotNull(
+        "Serialize",
+        "Deserialize".takeIf { deserialize },
+        "Clone",
+        "PartialEq",
+        "Eq".takeIf { hashable },
+        "Hash".takeIf { hashable },
+        "Default".takeIf { default },
+        "Debug",
+    ).plus(extraDerives).joinToString(prefix = "#[derive(", postfix = ")]")
+
+// Types reachable from the parameters of a method
+private fun List<DocSection>.findRustInputTypes(): Set<String> {
+    val allType = flatMap { section -> section.docTypes }
+    val typeMap = allType.associateBy { it.name }
+    val inputTypes = mutableSetOf<String>()
+    val queue = ArrayDeque(flatMap { section -> section.docMethods }
+        .flatMap { method -> method.docParameters }
+        .map { parameter -> parameter.type.getTypeWithoutGenerics() })
+    while (queue.isNotEmpty()) {
+        val type = queue.removeFirst()
+        if (type !is TelegramType.Super && type.name !in typeMap) continue
+        if (!inputTypes.add(type.name)) continue
+        if (type is TelegramType.Super) queue += type.rustVariants(allType).map { TelegramType.from(it.name) }
+        typeMap[type.name]?.docFields?.forEach { queue += it.type.getTypeWithoutGenerics() }
+    }
+    return inputTypes
+}
+
+private fun TelegramType.hasRustFloat(typesWithFloat: Set<String>): Boolean {
+    val type = getTypeWithoutGenerics()
+    return type.rustNumber == TelegramType.Float || type.name in typesWithFloat
+}
+
+private fun List<DocSection>.findRustTypesWithFloat(): Set<String> {
+    val allType = flatMap { section -> section.docTypes }
+    val allSuper = TelegramType.allSuper.filterIsInstance<TelegramType.Super>()
+    val typesWithFloat = mutableSetOf<String>()
+    do {
+        val count = typesWithFloat.size
+        allType.filter { type -> type.docFields.any { it.type.hasRustFloat(typesWithFloat) } }
+            .mapTo(typesWithFloat) { it.name }
+        allSuper.filter { type -> type.rustVariants(allType).any { it.name in typesWithFloat } }
+            .mapTo(typesWithFloat) { it.name }
+    } while (typesWithFloat.size != count)
+    return typesWithFloat
+}
+
+// Fields and variants closing a cycle without a Vec in between, e.g. "Message.reply_to_message"
+private fun List<DocSection>.findRustBoxed(): Set<String> {
+    val allType = flatMap { section -> section.docTypes }
+    val allSuper = TelegramType.allSuper.filterIsInstance<TelegramType.Super>()
+    val contained = allType.associate { type ->
+        type.name to type.docFields.filter { it.type !is TelegramType.ListType<*> }.map { it.type.name }
+    } + allSuper.associate { type -> type.name to type.rustVariants(allType).map { it.name } }
+
+    fun reaches(from: String, to: String): Boolean {
+        val visited = mutableSetOf<String>()
+        val queue = ArrayDeque(listOf(from))
+        while (queue.isNotEmpty()) {
+            val name = queue.removeFirst()
+            if (name == to) return true
+            if (visited.add(name)) queue += contained[name].orEmpty()
+        }
+        return false
+    }
+
+    return allType.flatMap { type ->
+        type.docFields.filter { it.type !is TelegramType.ListType<*> && reaches(it.type.name, type.name) }
+            .map { "${type.name}.${it.name}" }
+    }.plus(allSuper.flatMap { type ->
+        type.rustVariants(allType).filter { reaches(it.name, type.name) }.map { "${type.name}.${it.name}" }
+    }).toSet()
+}
+
+private val rustTagRegex = "(?:always|must be) (?:“([^”]+)”|<em>([^<]+)</em>)".toRegex()
+
+// Example: "Scope type, must be <em>default</em>" -> "type" to "default"
+private fun DocType.rustTag(): Pair<String, String>? = docFields.firstNotNullOfOrNull { field ->
+    if (field.name !in listOf("type", "status", "source")) return@firstNotNullOfOrNull null
+    rustTagRegex.find(field.description)?.let { match ->
+        field.name to (match.groups[1] ?: match.groups[2])!!.value
+    }
+}
+
+internal fun TelegramType.Super.toRustVariantName(type: DocType) =
+    type.name.removePrefix(name).takeIf { it.isNotEmpty() && it.first().isUpperCase() } ?: type.name
+
+// Only tagged subtypes are variants of a tagged type, e.g. ChatMemberUpdated isn't a ChatMember
+internal fun TelegramType.Super.rustVariants(allType: List<DocType>): List<DocType> {
+    val allSubtype = allType.filter { TelegramType.from(it.name).superType == this }
+    return allSubtype.filter { it.rustTag() != null }.ifEmpty { allSubtype }
+}
+
+private fun TelegramType.Super.toRustEnum(variants: List<DocType>, derives: String, boxed: Set<String>, allowDeprecated: Boolean) = buildString {
+    // the variant with more required fields is tried first, e.g. InputVenueMessageContent before InputLocationMessageContent
+    val tryOrder = variants.sortedByDescending { type -> type.docFields.count { it.required } }
+    val tagged = tryOrder.mapNotNull { type -> type.rustTag()?.let { tag -> type to tag.second } }
+    val tagField = variants.firstNotNullOfOrNull { it.rustTag() }?.first
+    appendLine("/**")
+    if (tagField != null) {
+        appendLine(" * One of ${variants.joinToString { "[${it.name}]" }}, discriminated by `$tagField`.")
+    } else {
+        appendLine(" * One of ${tryOrder.joinToString { "[${it.name}]" }}, tried in this order.")
+    }
+    appendLine(" *")
+    appendLine(" * Without the `strict` feature, an unknown variant is deserialized as `Unknown`.")
+    appendLine(" * */")
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine(derives)
+    appendLine("#[serde(untagged)]")
+    appendLine("#[allow(clippy::large_enum_variant)]")
+    appendLine("pub enum $name {")
+    variants.forEach { type ->
+        val payload = if ("$name.${type.name}" in boxed) "Box<${type.name}>" else type.name
+        appendLine("    ${toRustVariantName(type)}($payload),")
+    }
+    appendLine("    /// A variant not known to this version of the models, its content is discarded.")
+    appendLine("    #[cfg(not(feature = \"strict\"))]")
+    appendLine("    Unknown,")
+    appendLine("}")
+    appendLine()
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl<'de> Deserialize<'de> for $name {")
+    appendLine("    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {")
+    appendLine("        let value = serde_json::Value::deserialize(deserializer)?;")
+    fun tryVariants(types: List<DocType>, indent: String) = types.mapIndexed { index, type ->
+        val value = if (index == types.lastIndex) "value" else "value.clone()"
+        val deserialize = "from_json_value($value).map(Self::${toRustVariantName(type)})"
+        if (index == 0) deserialize else "\n$indent    .or_else(|_| $deserialize)"
+    }.joinToString("")
+    when {
+        tagField != null -> {
+            appendLine("        let result = match value.get(\"$tagField\").and_then(serde_json::Value::as_str) {")
+            tagged.groupBy({ it.second }, { it.first }).forEach { (tag, types) ->
+                appendLine("            Some(\"$tag\") => ${tryVariants(types, "            ")},")
+            }
+            appendLine("            #[cfg(not(feature = \"strict\"))]")
+            appendLine("            _ => Ok(Self::Unknown),")
+            appendLine("            #[cfg(feature = \"strict\")]")
+            appendLine("            tag => Err(serde::de::Error::custom(format_args!(\"unknown `$tagField` {:?} for $name\", tag))),")
+            appendLine("        };")
+        }
+
+        this@toRustEnum == TelegramType.Super.MaybeInaccessibleMessage -> {
+            // same rule of the Kotlin deserializer: inaccessible messages have date 0
+            val (inaccessible, accessible) = variants.partition { it.name == "InaccessibleMessage" }
+            appendLine("        let result = if value.get(\"date\").and_then(serde_json::Value::as_i64) == Some(0) {")
+            appendLine("            ${tryVariants(inaccessible, "            ")}")
+            appendLine("        } else {")
+            appendLine("            ${tryVariants(accessible, "            ")}")
+            appendLine("        };")
+        }
+
+        else -> {
+            appendLine("        let result = ${tryVariants(tryOrder, "        ")};")
+            appendLine("        #[cfg(not(feature = \"strict\"))]")
+            appendLine("        let result: Result<Self, serde_json::Error> = result.or(Ok(Self::Unknown));")
+        }
+    }
+    appendLine("        result.map_err(serde::de::Error::custom)")
+    appendLine("    }")
+    append("}")
+    variants.forEach { type ->
+        appendLine()
+        appendLine()
+        if (allowDeprecated) appendLine(rustAllowDeprecated)
+        appendLine("impl From<${type.name}> for $name {")
+        appendLine("    fn from(value: ${type.name}) -> Self {")
+        val value = if ("$name.${type.name}" in boxed) "Box::new(value)" else "value"
+        appendLine("        Self::${toRustVariantName(type)}($value)")
+        appendLine("    }")
+        append("}")
+    }
+}
+
+private fun DocType.toRustDoc(since: Map<String, String>) = buildString {
     appendLine("/**")
     appendLine(" * ${description.replace("\n", "\n * ")}")
     appendLine(" *")
+    since[name]?.let { version ->
+        appendLine(" * ${rustSince(version)}")
+        appendLine(" *")
+    }
     docFields.forEach {
         appendLine(" * @property ${it.name} ${it.description}")
     }
@@ -42,10 +869,28 @@ private fun DocType.toRustDoc() = buildString {
     append(" * */")
 }
 
-private fun DocMethod.toRustDoc(showReturn: Boolean = true) = buildString {
+// Example: "if the edited message is not an inline message, the edited Message is returned, otherwise True is returned"
+private fun DocMethod.rustReturnsEditResult() =
+    returns.name == "Message" && "otherwise <em>True</em> is returned" in description
+
+private fun DocMethod.toRustResponse() = if (rustReturnsEditResult()) "EditResult" else returns.toRustType()
+
+private fun DocMethod.toRustMethodImpl(allowDeprecated: Boolean) = buildString {
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl TelegramMethod for ${name.capitalize()}Request {")
+    appendLine("    type Response = ${toRustResponse()};")
+    appendLine("    const NAME: &'static str = \"$name\";")
+    append("}")
+}
+
+private fun DocMethod.toRustDoc(since: Map<String, String>, showReturn: Boolean = true) = buildString {
     appendLine("/**")
     appendLine(" * ${description.replace("\n", "\n * ")}")
     appendLine(" *")
+    since[name]?.let { version ->
+        appendLine(" * ${rustSince(version)}")
+        appendLine(" *")
+    }
     docParameters.forEach {
         appendLine(" * @property ${it.name} ${it.description}")
     }
@@ -56,28 +901,135 @@ private fun DocMethod.toRustDoc(showReturn: Boolean = true) = buildString {
     append(" * */")
 }
 
-private fun DocType.toRustDataClass() = buildString {
-    appendLine("#[derive(Serialize, Deserialize, Clone, PartialEq, PartialOrd, Debug)]")
+private fun rustSince(version: String) = "Since Bot API $version."
+
+// Example: "<em>Optional</em>. <em>Deprecated</em>. Use <em>thumbnail</em> instead." or "new bots should use the permissions field instead"
+private val rustDeprecatedRegex = "\\bdeprecated\\b|\\bnew bots should use\\b|\\buse \\w+ instead\\b"
+    .toRegex(RegexOption.IGNORE_CASE)
+
+// Example: "Deprecated. Use thumbnail instead." -> "Use thumbnail instead."
+private fun String.toRustDeprecationNote(): String? {
+    val text = replace("<[^>]+>".toRegex(), "").replace("\n", " ")
+    val sentences = text.split("(?<=\\.) ".toRegex()).filter { rustDeprecatedRegex.containsMatchIn(it) }
+    if (sentences.isEmpty()) return null
+    return sentences.filter { !it.trim().equals("deprecated.", ignoreCase = true) }.joinToString(" ").trim()
+        .ifEmpty { "Deprecated by the Bot API." }
+}
+
+// Example: "Deprecated. Use thumbnail instead." -> #[deprecated(note = "Use thumbnail instead.")]
+private fun String.toRustDeprecated(): String? = toRustDeprecationNote()?.let { note ->
+    "#[deprecated(note = \"${note.replace("\\", "\\\\").replace("\"", "\\\"")}\")]"
+}
+
+/**
+ * Note of every item marked `#[deprecated]` by [toRustModels], e.g. "Message.thumb" to "Use thumbnail instead.".
+ */
+internal fun List<DocSection>.toRustDeprecations(): Map<String, String> = flatMap { section ->
+    section.docTypes.flatMap { type ->
+        listOf(type.name to type.description) + type.docFields.map { "${type.name}.${it.name}" to it.description }
+    } + section.docMethods.flatMap { method ->
+        listOf(method.name to method.description) + method.docParameters.map { "${method.name}.${it.name}" to it.description }
+    }
+}.mapNotNull { (name, description) -> description.toRustDeprecationNote()?.let { name to it } }.toMap()
+
+/**
+ * What the Rust API of each item is generated from by [toRustModels], including the mappings read from the descriptions:
+ * the derives of a type, the Rust type of a field or parameter (units, targets and boxes) and the response of a method,
+ * e.g. "Message.date" to "UnixTime", "editMessageText.chat_id" to "MessageTarget" and "editMessageText" to "EditResult".
+ */
+internal fun List<DocSection>.toRustSignatures(): Map<String, String> {
+    val sections = map { section -> section.withRustUnits() }
+    val typesWithFloat = sections.findRustTypesWithFloat()
+    val inputTypes = sections.findRustInputTypes()
+    val targets = sections.findRustTargets()
+    val boxed = sections.findRustBoxed()
+    val signatures = mutableMapOf<String, String>()
+    sections.flatMap { section -> section.docTypes }.forEach { type ->
+        signatures[type.name] = rustDerives(
+            deserialize = true,
+            hashable = type.name !in typesWithFloat,
+            default = type.name in inputTypes && type.docFields.none { it.required },
+            extraDerives = emptyList()
+        )
+        type.docFields.forEach { field ->
+            signatures["${type.name}.${field.name}"] = field.toRustType(boxed = "${type.name}.${field.name}" in boxed)
+        }
+    }
+    sections.flatMap { section -> section.docMethods }.forEach { method ->
+        val target = targets.firstOrNull { method in it.methods }
+        val targetNames = target?.groups?.flatten()?.map { it.name }.orEmpty()
+        signatures[method.name] = method.toRustResponse()
+        method.docParameters.forEach { parameter ->
+            signatures["${method.name}.${parameter.name}"] =
+                if (parameter.name in targetNames) target!!.rustTypeFor(method) else parameter.toRustType()
+        }
+    }
+    return signatures
+}
+
+/**
+ * Generated code keeps using deprecated items without warnings, only the code using the models gets them:
+ * the items of a type or a request using a deprecated item get this attribute. The hand-written sections
+ * (commands, dispatch, ...) read a few core fields and keep their warnings, they have to be migrated with the docs.
+ */
+internal const val rustAllowDeprecated = "#[allow(deprecated)]"
+
+// Items used by the generated code of a type: the type, its fields and their types
+internal fun DocType.rustUses() = listOf(name) + docFields.flatMap { listOf("$name.${it.name}", it.type.getTypeWithoutGenerics().name) }
+
+// Items used by the generated code of a request: the method, its parameters, their types and the response type
+internal fun DocMethod.rustUses() = listOf(name, returns.getTypeWithoutGenerics().name) +
+    docParameters.flatMap { listOf("$name.${it.name}", it.type.getTypeWithoutGenerics().name) }
+
+private fun DocType.toRustDataClass(derives: String, boxed: Set<String>, since: Map<String, String>, allowDeprecated: Boolean) = buildString {
+    description.toRustDeprecated()?.let { appendLine(it) }
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine(derives)
+    appendLine("#[cfg_attr(feature = \"strict\", serde(deny_unknown_fields))]")
     appendLine("pub struct $name {")
     docFields.forEachIndexed { index, field ->
         appendLine("    /// ${field.description}")
+        since["$name.${field.name}"]?.let { version ->
+            appendLine("    ///")
+            appendLine("    /// ${rustSince(version)}")
+        }
+        field.description.toRustDeprecated()?.let { appendLine("    $it") }
         if (!field.required) appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+        field.type.toRustSerdeWith(field.required)?.let { appendLine("    $it") }
         if (field.name == "type") {
             appendLine("    #[serde(rename = \"type\")]")
-            append("    pub type_: ${field.toRustType()}")
+            append("    pub type_: ${field.toRustType(boxed = "$name.${field.name}" in boxed)}")
         } else {
-            append("    pub ${field.name}: ${field.toRustType()}")
+            append("    pub ${field.name}: ${field.toRustType(boxed = "$name.${field.name}" in boxed)}")
         }
         if (index == docFields.lastIndex) appendLine() else appendLine(",")
     }
     append("}")
 }
 
-private fun DocMethod.toRustDataClass() = buildString {
-    appendLine("#[derive(Serialize, Deserialize, Clone, PartialEq, PartialOrd, Debug)]")
+private fun DocMethod.toRustDataClass(
+    derives: String,
+    since: Map<String, String>,
+    flattenTypes: Set<String>,
+    allowDeprecated: Boolean
+) = buildString {
+    fun DocParameter.isFlatten() = type.name.substringBefore('<') in flattenTypes
+    description.toRustDeprecated()?.let { appendLine(it) }
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine(derives)
+    // serde doesn't support deny_unknown_fields together with flatten
+    if (docParameters.none { it.isFlatten() }) appendLine("#[cfg_attr(feature = \"strict\", serde(deny_unknown_fields))]")
     appendLine("pub struct ${name.capitalize()}Request {")
     docParameters.forEachIndexed { index, field ->
         appendLine("    /// ${field.description}")
+        since["$name.${field.name}"]?.let { version ->
+            appendLine("    ///")
+            appendLine("    /// ${rustSince(version)}")
+        }
+        field.description.toRustDeprecated()?.let { appendLine("    $it") }
+        if (!field.required) appendLine("    #[serde(skip_serializing_if = \"Option::is_none\")]")
+        if (field.isFlatten()) appendLine("    #[serde(flatten)]")
+        field.type.toRustSerdeWith(field.required)?.let { appendLine("    $it") }
         if (field.name == "type") {
             appendLine("    #[serde(rename = \"type\")]")
             append("    pub type_: ${field.toRustType()}")
@@ -89,8 +1041,526 @@ private fun DocMethod.toRustDataClass() = buildString {
     append("}")
 }
 
-private fun DocField.toRustType() =
-    if (required) type.toRustType() else "Option<${type.toRustType()}>"
+// Example: "Required if <em>inline_message_id</em> is not specified." -> ["inline_message_id"]
+private val rustRequiredIfNotRegex = "^Required if (.+?) (?:is|are) not specified\\. ?".toRegex()
+
+private fun DocParameter.rustRequiredIfNot(): Set<String>? = rustRequiredIfNotRegex.find(description)
+    ?.let { match -> "<em>(\\w+)</em>".toRegex().findAll(match.groupValues[1]).map { it.groupValues[1] }.toSet() }
+
+// Groups of parameters required exactly when the other groups are not specified
+private fun DocMethod.rustExclusiveGroups(): List<List<DocParameter>>? {
+    val conditional = docParameters.mapNotNull { parameter -> parameter.rustRequiredIfNot()?.let { parameter to it } }
+    val allNames = conditional.map { it.first.name }.toSet()
+    val groups = conditional.groupBy({ it.second }, { it.first })
+    val exclusive = groups.all { (others, group) -> others == allNames - group.map { it.name }.toSet() }
+    return groups.values.toList().takeIf { exclusive && it.size > 1 }
+}
+
+/**
+ * Mutually exclusive parameters shared by [methods], e.g. chat_id + message_id or inline_message_id.
+ * Parameters with a different type in some methods become type parameters (chat_id is an Integer in games).
+ */
+private class RustTarget(val groups: List<List<DocParameter>>, val methods: List<DocMethod>) {
+    val name = if (groups.all { group -> group.any { it.name.endsWith("message_id") } }) {
+        "MessageTarget"
+    } else {
+        "${methods.first().name.capitalize()}Target"
+    }
+
+    val typeParameters: Map<String, String> = groups.flatten().mapNotNull { parameter ->
+        val types = methods.map { it.rustTypeOf(parameter.name) }.distinct()
+        if (types.size > 1) parameter.name to types.first() else null
+    }.toMap()
+
+    fun variantName(group: List<DocParameter>) =
+        group.first().name.removeSuffix("_id").removeSuffix("_message").toRustCamelCase()
+
+    fun rustTypeOf(parameter: DocParameter) =
+        if (parameter.name in typeParameters) parameter.name.toRustCamelCase() else parameter.type.toRustType()
+
+    fun rustTypeFor(method: DocMethod): String {
+        val arguments = typeParameters.keys.map { method.rustTypeOf(it) }
+        return if (arguments == typeParameters.values.toList()) name else "$name<${arguments.joinToString()}>"
+    }
+
+    private fun DocMethod.rustTypeOf(parameterName: String) =
+        docParameters.first { it.name == parameterName }.type.toRustType()
+}
+
+private fun List<DocSection>.findRustTargets(): List<RustTarget> = flatMap { section -> section.docMethods }
+    .mapNotNull { method -> method.rustExclusiveGroups()?.let { method to it } }
+    .groupBy { (_, groups) -> groups.map { group -> group.map { it.name } } }
+    .map { (_, methods) -> RustTarget(methods.first().second, methods.map { it.first }) }
+
+private fun DocMethod.withRustTarget(target: RustTarget): DocMethod {
+    val names = target.groups.flatten().map { it.name }.toSet()
+    val index = docParameters.indexOfFirst { it.name in names }
+    val parameter = DocParameter(
+        name = "target",
+        description = target.groups.joinToString(" or ", prefix = "Target of the request: ") { group ->
+            group.joinToString(" and ") { "<em>${it.name}</em>" }
+        },
+        type = TelegramType.Declared(target.rustTypeFor(this)),
+        required = true
+    )
+    val parameters = docParameters.filter { it.name !in names }.toMutableList().apply { add(index, parameter) }
+    return copy(docParameters = parameters.sortedByDescending { it.required })
+}
+
+private fun RustTarget.toRustEnum(derives: String) = buildString {
+    val generics = if (typeParameters.isEmpty()) "" else typeParameters.entries.joinToString(prefix = "<", postfix = ">") {
+        "${it.key.toRustCamelCase()} = ${it.value}"
+    }
+    val genericNames = if (typeParameters.isEmpty()) "" else typeParameters.keys.joinToString(prefix = "<", postfix = ">") {
+        it.toRustCamelCase()
+    }
+    appendLine("/**")
+    appendLine(" * Mutually exclusive parameters of ${methods.joinToString { "[${it.name.capitalize()}Request]" }}.")
+    appendLine(" * */")
+    appendLine(derives)
+    appendLine("#[serde(untagged)]")
+    appendLine("pub enum $name$generics {")
+    groups.forEach { group ->
+        appendLine("    ${variantName(group)} {")
+        group.forEach { parameter ->
+            appendLine("        /// ${parameter.description.replace(rustRequiredIfNotRegex, "")}")
+            appendLine("        ${parameter.name}: ${rustTypeOf(parameter)},")
+        }
+        appendLine("    },")
+    }
+    appendLine("}")
+    appendLine()
+    appendLine("impl$genericNames $name$genericNames {")
+    groups.forEachIndexed { index, group ->
+        val variant = variantName(group)
+        if (index > 0) appendLine()
+        appendLine("    /// Creates a [$name::$variant].")
+        appendLine("    pub fn ${variant.replaceFirstChar { it.lowercase() }.toRustSnakeCase()}(${group.joinToString { "${it.name}: impl Into<${rustTypeOf(it)}>" }}) -> Self {")
+        appendLine("        Self::$variant { ${group.joinToString { "${it.name}: ${it.name}.into()" }} }")
+        appendLine("    }")
+    }
+    append("}")
+}
+
+internal fun String.toRustCamelCase() = split('_').joinToString("") { it.capitalize() }
+
+private const val rustDuration = "std::time::Duration"
+
+private val rustUnitRegex = "^(Meters|Degrees)<(Integer|Float)>$".toRegex()
+
+/**
+ * Numbers with a documented unit become typed values:
+ * - "Unix time" is a UnixTime, or an Until when 0 means forever
+ * - "in seconds" is a [rustDuration], `live_period` a LivePeriod because of the 0x7FFFFFFF sentinel
+ * - "in meters" and "in degrees" are Meters and Degrees of the documented number type
+ */
+private fun DocSection.withRustUnits(): DocSection {
+    fun TelegramType.rustUnit(name: String, description: String): TelegramType {
+        if (this != TelegramType.Integer && this != TelegramType.Float) return this
+        val text = description.replace("<[^>]+>".toRegex(), "").lowercase()
+        return when {
+            this == TelegramType.Integer && "unix time" in text ->
+                // Example: "Date when restrictions will be lifted for this user; Unix time. If 0, then the user is restricted forever"
+                TelegramType.Declared(if ("forever" in text) "Until" else "UnixTime")
+
+            this == TelegramType.Integer && name == "live_period" -> TelegramType.Declared("LivePeriod")
+            // Example: "Duration of the video in seconds", "the number of seconds left to wait"
+            this == TelegramType.Integer && ("in seconds" in text || "seconds left" in text) -> TelegramType.Declared(rustDuration)
+            "in meters" in text -> TelegramType.Declared("Meters<${this.name}>")
+            "in degrees" in text -> TelegramType.Declared("Degrees<${this.name}>")
+            else -> this
+        }
+    }
+    return copy(
+        docTypes = docTypes.map { type ->
+            type.copy(docFields = type.docFields.map { it.copy(type = it.type.rustUnit(it.name, it.description)) })
+        },
+        docMethods = docMethods.map { method ->
+            method.copy(docParameters = method.docParameters.map { it.copy(type = it.type.rustUnit(it.name, it.description)) })
+        }
+    )
+}
+
+// Number sent for a unit, e.g. Float for Meters<Float> and Integer (seconds) for a Duration
+private val TelegramType.rustNumber: TelegramType
+    get() = when {
+        name == rustDuration -> TelegramType.Integer
+        else -> rustUnitRegex.find(name)?.let { TelegramType.from(it.groupValues[2]) } ?: this
+    }
+
+// Example: "Meters(x)" for a Meters<Float>
+internal fun TelegramType.toRustUnitValue(number: String) = when {
+    name == rustDuration -> "$rustDuration::from_secs($number)"
+    rustUnitRegex.matches(name) -> "${name.substringBefore('<')}($number)"
+    else -> number
+}
+
+// Example: ".as_secs()" to compare a Duration with a documented range
+private fun TelegramType.toRustNumberAccessor() = when {
+    name == rustDuration -> ".as_secs()"
+    rustUnitRegex.matches(name) -> ".0"
+    else -> ""
+}
+
+private fun TelegramType.toRustConstant(value: Long) =
+    toRustUnitValue(if (rustNumber == TelegramType.Float) "$value.0" else "$value")
+
+// serialize_with and deserialize_with instead of `with`: derives such as schemars::JsonSchema read `with` as a type
+private fun TelegramType.toRustSerdeWith(required: Boolean): String? {
+    val adapter = when (name) {
+        "UnixTime" -> "unix_time"
+        rustDuration -> "seconds"
+        else -> return null
+    }
+    val module = if (required) adapter else "$adapter::option"
+    val default = if (required) "" else ", default"
+    return "#[serde(serialize_with = \"$module::serialize\", deserialize_with = \"$module::deserialize\"$default)]"
+}
+
+private val rustRangeRegex = "(?<![\\w.-])(\\d+)-(\\d+)(?![\\d-])(?: (characters|bytes))?".toRegex(RegexOption.IGNORE_CASE)
+private val rustDefaultRegex = "defaults to (\\w+)".toRegex(RegexOption.IGNORE_CASE)
+
+/**
+ * Documented limit of a field, e.g. "Values between 1-100 are accepted" or "1-4096 characters after entities parsing".
+ *
+ * @param unit "characters", "bytes" or "items" for a length, null for a value
+ */
+internal class RustLimit(val min: Long, val max: Long, val unit: String?)
+
+private val DocField.rustDescriptionText get() = description.replace("<[^>]+>".toRegex(), "")
+
+internal fun DocField.rustLimit(): RustLimit? = rustRangeRegex.findAll(rustDescriptionText)
+    .map { match -> Triple(match.groupValues[1].toLong(), match.groupValues[2].toLong(), match.groupValues[3].lowercase()) }
+    .filter { (min, max, _) -> min <= max }
+    .mapNotNull { (min, max, unit) ->
+        when (type.rustNumber) {
+            is TelegramType.ListType<*> -> RustLimit(min, max, "items")
+            TelegramType.StringType -> if (unit.isEmpty()) null else RustLimit(min, max, unit)
+            TelegramType.Integer, TelegramType.Float -> if (unit.isEmpty()) RustLimit(min, max, null) else null
+            else -> null
+        }
+    }
+    .firstOrNull()
+
+private fun DocField.rustDefault(): String? {
+    val value = rustDefaultRegex.find(rustDescriptionText)?.groupValues?.get(1) ?: return null
+    return when (type.rustNumber) {
+        TelegramType.Integer -> value.takeIf { it.all(Char::isDigit) }?.let { type.toRustConstant(it.toLong()) }
+        TelegramType.Boolean -> value.lowercase().takeIf { it == "true" || it == "false" }
+        else -> null
+    }
+}
+
+private fun TelegramType.Super.toRustValidate(variants: List<DocType>, allowDeprecated: Boolean) = buildString {
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl Validate for $name {")
+    appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>) {")
+    appendLine("        match self {")
+    variants.forEach { type ->
+        appendLine("            Self::${toRustVariantName(type)}(value) => value.violations(path, violations),")
+    }
+    appendLine("            #[cfg(not(feature = \"strict\"))]")
+    appendLine("            Self::Unknown => {}")
+    appendLine("        }")
+    appendLine("    }")
+    append("}")
+}
+
+/**
+ * Constants for the documented limits and defaults, and the [Validate] implementation checking the limits.
+ * Fields of [inputTypes] are validated recursively.
+ */
+private fun List<DocField>.toRustValidate(structName: String, inputTypes: Set<String>, allowDeprecated: Boolean) = buildString {
+    val fields = this@toRustValidate
+    val limits = fields.mapNotNull { field -> field.rustLimit()?.let { field to it } }
+    val defaults = fields.mapNotNull { field -> field.rustDefault()?.let { field to it } }
+    val nested = fields.filter { field ->
+        val type = field.type.getTypeWithoutGenerics()
+        (type is TelegramType.Declared || type is TelegramType.Super) && type.name in inputTypes
+    }
+    if (limits.isNotEmpty() || defaults.isNotEmpty()) {
+        if (allowDeprecated) appendLine(rustAllowDeprecated)
+        appendLine("impl $structName {")
+        limits.forEach { (field, limit) ->
+            val constant = field.rustName.uppercase()
+            if (limit.unit == null) {
+                val rustType = field.type.toRustType()
+                appendLine("    /// Minimum value of `${field.name}`.")
+                appendLine("    pub const ${constant}_MIN: $rustType = ${field.type.toRustConstant(limit.min)};")
+                appendLine("    /// Maximum value of `${field.name}`.")
+                appendLine("    pub const ${constant}_MAX: $rustType = ${field.type.toRustConstant(limit.max)};")
+            } else {
+                appendLine("    /// Minimum length of `${field.name}`, in ${limit.unit}.")
+                appendLine("    pub const ${constant}_MIN_LENGTH: usize = ${limit.min};")
+                appendLine("    /// Maximum length of `${field.name}`, in ${limit.unit}.")
+                appendLine("    pub const ${constant}_MAX_LENGTH: usize = ${limit.max};")
+            }
+        }
+        defaults.forEach { (field, value) ->
+            appendLine("    /// Value used by Telegram when `${field.name}` is not specified.")
+            appendLine("    pub const ${field.rustName.uppercase()}_DEFAULT: ${field.type.toRustType()} = $value;")
+        }
+        appendLine("}")
+        appendLine()
+    }
+    val checks = limits.map { (field, limit) ->
+        val constant = "Self::${field.rustName.uppercase()}"
+        val path = "field_path(path, \"${field.name}\")"
+        val value = if (field.required) "self.${field.rustName}" else "value"
+        val check = when (limit.unit) {
+            null -> {
+                // units are compared by their number, e.g. the seconds of a Duration
+                val accessor = field.type.toRustNumberAccessor()
+                val number = if (field.required || accessor.isNotEmpty()) "$value$accessor" else "*$value"
+                "check_range(violations, $path, $number, ${constant}_MIN$accessor, ${constant}_MAX$accessor);"
+            }
+            "characters" -> "check_length(violations, $path, $value.encode_utf16().count(), ${constant}_MIN_LENGTH, ${constant}_MAX_LENGTH, \"characters\");"
+            else -> "check_length(violations, $path, $value.len(), ${constant}_MIN_LENGTH, ${constant}_MAX_LENGTH, \"${limit.unit}\");"
+        }
+        // "after entities parsing": the length is known only when the entities are given explicitly
+        val parseMode = listOf("${field.name}_parse_mode", "parse_mode")
+            .firstOrNull { name -> fields.any { it.name == name } }
+            ?.takeIf { "after entities parsing" in field.description }
+        val condition = when {
+            field.required && parseMode == null -> null
+            field.required -> "if self.$parseMode.is_none() {"
+            parseMode == null -> "if let Some(value) = &self.${field.rustName} {"
+            else -> "if let (None, Some(value)) = (&self.$parseMode, &self.${field.rustName}) {"
+        }
+        if (condition == null) listOf(check) else listOf(condition, "    $check", "}")
+    } + nested.map { field -> listOf("self.${field.rustName}.violations(&field_path(path, \"${field.name}\"), violations);") }
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl Validate for $structName {")
+    if (checks.isEmpty()) {
+        appendLine("    fn violations(&self, _path: &str, _violations: &mut Vec<Violation>) {}")
+    } else {
+        appendLine("    fn violations(&self, path: &str, violations: &mut Vec<Violation>) {")
+        checks.flatten().forEach { appendLine("        $it") }
+        appendLine("    }")
+    }
+    append("}")
+}
+
+private val DocField.rustName get() = if (name == "type") "type_" else name
+
+private fun List<DocField>.toRustBuilder(
+    structName: String,
+    tag: Pair<String, String>?,
+    boxed: Set<String> = emptySet(),
+    allowDeprecated: Boolean
+) = buildString {
+    // Box<T> implements From<T>, setters of boxed fields still accept a T
+    fun DocField.rustInnerType() = if ("$structName.$name" in boxed) "Box<${type.toRustType()}>" else type.toRustType()
+    val required = this@toRustBuilder.filter { it.required && it.name != tag?.first }
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl $structName {")
+    appendLine("    /// Creates a [$structName] with the required fields, the optional ones are `None`.")
+    if (required.isEmpty()) appendLine("    #[allow(clippy::new_without_default)]")
+    if (required.size > 7) appendLine("    #[allow(clippy::too_many_arguments)]")
+    appendLine("    pub fn new(${required.joinToString { "${it.rustName}: impl Into<${it.rustInnerType()}>" }}) -> Self {")
+    appendLine("        Self {")
+    this@toRustBuilder.forEach { field ->
+        val value = when {
+            tag != null && field.name == tag.first -> "String::from(\"${tag.second}\")"
+            field.required -> "${field.rustName}.into()"
+            else -> "None"
+        }
+        appendLine("            ${field.rustName}: $value,")
+    }
+    appendLine("        }")
+    appendLine("    }")
+    this@toRustBuilder.filter { !it.required }.forEach { field ->
+        appendLine()
+        appendLine("    /// Sets `${field.name}`.")
+        appendLine("    pub fn ${field.rustName}(mut self, ${field.rustName}: impl Into<${field.rustInnerType()}>) -> Self {")
+        appendLine("        self.${field.rustName} = Some(${field.rustName}.into());")
+        appendLine("        self")
+        appendLine("    }")
+    }
+    append("}")
+}
+
+// Example: "Exactly one of the optional fields must be used to specify type of the button."
+private val rustExclusiveRegex = "\\b(?:exactly|at most) one of the optional fields\\b".toRegex(RegexOption.IGNORE_CASE)
+
+/**
+ * Constructors of the types using one optional field at a time, one per field, e.g. `InlineKeyboardButton::new_url(text, url)`.
+ * Boolean fields are set to true, e.g. `KeyboardButton::new_request_contact(text)`.
+ */
+private fun DocType.toRustExclusiveConstructors(allowDeprecated: Boolean): String? {
+    if (!rustExclusiveRegex.containsMatchIn(description)) return null
+    val required = docFields.filter { it.required && it.name != rustTag()?.first }
+    val arguments = required.joinToString { "${it.rustName}: impl Into<${it.type.toRustType()}>" }
+    val new = "Self::new(${required.joinToString { it.rustName }})"
+    return buildString {
+        if (allowDeprecated) appendLine(rustAllowDeprecated)
+        appendLine("impl $name {")
+        docFields.filter { !it.required }.forEachIndexed { index, field ->
+            if (index > 0) appendLine()
+            if (field.type == TelegramType.Boolean) {
+                appendLine("    /// Creates a [$name] with `${field.name}` set to true, the other optional fields are `None`.")
+                appendLine("    pub fn new_${field.name}($arguments) -> Self {")
+                appendLine("        $new.${field.rustName}(true)")
+            } else {
+                val separator = if (required.isEmpty()) "" else ", "
+                appendLine("    /// Creates a [$name] with `${field.name}`, the other optional fields are `None`.")
+                appendLine("    pub fn new_${field.name}($arguments$separator${field.rustName}: impl Into<${field.type.toRustType()}>) -> Self {")
+                appendLine("        $new.${field.rustName}(${field.rustName})")
+            }
+            appendLine("    }")
+        }
+        append("}")
+    }
+}
+
+private fun TelegramType.Super.toRustArbitrary(variants: List<DocType>, allowDeprecated: Boolean) = buildString {
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl<'a> arbitrary::Arbitrary<'a> for $name {")
+    appendLine("    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {")
+    if (variants.size == 1) {
+        appendLine("        Ok(Self::${toRustVariantName(variants.single())}(u.arbitrary()?))")
+    } else {
+        appendLine("        Ok(match u.int_in_range(0..=${variants.lastIndex})? {")
+        variants.forEachIndexed { index, type ->
+            val pattern = if (index == variants.lastIndex) "_" else "$index"
+            appendLine("            $pattern => Self::${toRustVariantName(type)}(u.arbitrary()?),")
+        }
+        appendLine("        })")
+    }
+    appendLine("    }")
+    append("}")
+}
+
+/**
+ * Arbitrary values respecting the tag of the variants and the documented limits,
+ * so that they are deserialized back to the same type.
+ */
+private fun DocType.toRustArbitrary(allowDeprecated: Boolean) = buildString {
+    val tag = rustTag()
+    // same rule of the MaybeInaccessibleMessage deserializer: only inaccessible messages have date 0
+    val nonZeroDate = TelegramType.from(name).superType == TelegramType.Super.MaybeInaccessibleMessage &&
+            docFields.none { it.name == "date" && rustAlwaysRegex.containsMatchIn(it.description) }
+    val values = docFields.map { field ->
+        field.rustName to (field.rustArbitraryConstant(tag) ?: field.toRustArbitrary(nonZeroDate && field.name == "date"))
+    }
+    val unstructured = if (docFields.all { it.rustArbitraryConstant(tag) != null }) "_u" else "u"
+    appendLine("#[cfg(feature = \"arbitrary\")]")
+    if (allowDeprecated) appendLine(rustAllowDeprecated)
+    appendLine("impl<'a> arbitrary::Arbitrary<'a> for $name {")
+    appendLine("    fn arbitrary($unstructured: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {")
+    appendLine("        Ok(Self {")
+    values.forEach { (name, value) ->
+        appendLine("            $name: $value,")
+    }
+    appendLine("        })")
+    appendLine("    }")
+    append("}")
+}
+
+// Example: the tag of a variant, or "Always 0"
+private fun DocField.rustArbitraryConstant(tag: Pair<String, String>?): String? = when {
+    tag != null && name == tag.first -> "String::from(\"${tag.second}\")"
+    type == TelegramType.Integer -> rustAlwaysRegex.find(description)?.groupValues?.get(1)
+    else -> null
+}
+
+private fun DocField.toRustArbitrary(nonZero: Boolean): String {
+    val limit = rustLimit()
+    val number = type.rustNumber
+    val value = when {
+        type.name == "UnixTime" -> "unix_time::arbitrary(u)?"
+        limit == null && number == TelegramType.Float -> type.toRustUnitValue("arbitrary_float(u, -1e6, 1e6)? as Float")
+        limit == null && type.name == rustDuration -> type.toRustUnitValue("u.int_in_range(0..=u64::from(u32::MAX))?")
+        limit == null && nonZero -> "u.int_in_range(1..=Integer::MAX)?"
+        limit == null && number != type -> type.toRustUnitValue("u.arbitrary()?")
+        limit == null -> return "u.arbitrary()?"
+        limit.unit == null && number == TelegramType.Float ->
+            type.toRustUnitValue("arbitrary_float(u, ${limit.min}.0, ${limit.max}.0)? as Float")
+
+        limit.unit == null -> type.toRustUnitValue("u.int_in_range(${limit.min}..=${limit.max})?")
+        limit.unit == "items" -> "arbitrary_vec(u, ${limit.min}, ${limit.max})?"
+        limit.unit == "bytes" -> "arbitrary_string(u, ${limit.min}, ${limit.max}, char::len_utf8)?"
+        else -> "arbitrary_string(u, ${limit.min}, ${limit.max}, char::len_utf16)?"
+    }
+    return if (required) value else "if u.arbitrary()? { Some($value) } else { None }"
+}
+
+private fun String.toRustSnakeCase() = replace("[A-Z]".toRegex()) { "_" + it.value.lowercase() }
+
+private val rustGson = GsonBuilder().setPrettyPrinting().disableHtmlEscaping().create()
+
+private val rustAlwaysRegex = "^Always (\\d+)\\b".toRegex()
+
+/**
+ * Values synthesized from the field types: nested objects are minimal, a `Super` is its first variant
+ * and types without fields (e.g. CallbackGame) are `{}`. Returns null for types without a JSON form, e.g. InputFile.
+ */
+private class RustFixtures(sections: List<DocSection>) {
+    private val allType = sections.flatMap { section -> section.docTypes }
+    private val typeMap = allType.associateBy { it.name }
+    private val visiting = mutableSetOf<String>()
+
+    fun minimal(type: DocType): JsonObject? {
+        // a type containing itself can't be built from required fields only
+        if (!visiting.add(type.name)) return null
+        try {
+            return fixture(type, full = false)
+        } finally {
+            visiting.remove(type.name)
+        }
+    }
+
+    fun full(type: DocType): JsonObject? = if (type.docFields.all { it.required }) null else fixture(type, full = true)
+
+    /**
+     * Parameters of [method]: the required ones and the first group of the mutually exclusive ones,
+     * with [nested] also the first optional object, e.g. a `reply_markup`. Returns null when there is no such JSON,
+     * e.g. for a required InputFile or without optional objects.
+     */
+    fun request(method: DocMethod, nested: Boolean): JsonObject? {
+        val exclusive = method.rustExclusiveGroups()?.first().orEmpty().toSet()
+        val json = JsonObject()
+        method.docParameters.filter { it.required || it in exclusive }.forEach { parameter ->
+            json.add(parameter.name, value(parameter.type, parameter.description) ?: return null)
+        }
+        if (nested) {
+            val (name, value) = method.docParameters.filter { !it.required && it !in exclusive }.firstNotNullOfOrNull { parameter ->
+                value(parameter.type, parameter.description)?.takeIf { it is JsonObject && it.size() > 0 }?.let { parameter.name to it }
+            } ?: return null
+            json.add(name, value)
+        }
+        return json
+    }
+
+    private fun fixture(type: DocType, full: Boolean): JsonObject? {
+        val json = JsonObject()
+        type.docFields.filter { full || it.required }.forEach { field ->
+            val value = value(field.type, field.description)
+            if (value != null) json.add(field.name, value) else if (field.required) return null
+        }
+        return json
+    }
+
+    private fun value(type: TelegramType, description: String): JsonElement? = when (type) {
+        is TelegramType.ListType<*> -> value(type.elementType, description)?.let { element -> JsonArray().apply { add(element) } }
+        // Example: "Always 0. The field can be used to differentiate regular and inaccessible messages."
+        TelegramType.Integer -> JsonPrimitive(rustAlwaysRegex.find(description)?.groupValues?.get(1)?.toLong() ?: 1L)
+        TelegramType.Float -> JsonPrimitive(1.5)
+        TelegramType.Boolean -> JsonPrimitive(true)
+        TelegramType.StringType -> JsonPrimitive(rustTagRegex.find(description)?.let { (it.groups[1] ?: it.groups[2])!!.value } ?: "string")
+        TelegramType.ParseMode -> JsonPrimitive("HTML")
+        TelegramType.InputFile -> null
+        is TelegramType.WithAlternative -> JsonPrimitive("string")
+        is TelegramType.Super -> type.rustVariants(allType).firstNotNullOfOrNull { minimal(it) }
+        else -> typeMap[type.name]?.let { minimal(it) } ?: JsonObject()
+    }
+}
+
+private fun DocField.toRustType(boxed: Boolean = false): String {
+    val rustType = if (boxed) "Box<${type.toRustType()}>" else type.toRustType()
+    return if (required) rustType else "Option<$rustType>"
+}
 
 private fun DocParameter.toRustType() =
     if (required) type.toRustType() else "Option<${type.toRustType()}>"
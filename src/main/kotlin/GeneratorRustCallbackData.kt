@@ -0,0 +1,255 @@
+/**
+ * Typed `callback_data` with the `callback-data` feature: `CallbackData` encodes serde values in base64url
+ * after a prefix identifying their type and version, within the 64 bytes limit or behind the key of a `CallbackDataStore`.
+ */
+fun List<DocSection>.toRustCallbackData() = buildString {
+    append(rustCallbackData())
+}
+
+private fun rustCallbackData() = buildString {
+    appendLine("/// Typed `callback_data`, see [CallbackData].")
+    appendLine("#[cfg(feature = \"callback-data\")]")
+    appendLine("pub use callback_data::*;")
+    appendLine()
+    appendLine("#[cfg(feature = \"callback-data\")]")
+    appendLine("mod callback_data {")
+    appendLine("    use super::*;")
+    appendLine()
+    appendLine("    /// Maximum length of `callback_data`, in bytes.")
+    appendLine("    pub const CALLBACK_DATA_MAX_LENGTH: usize = 64;")
+    appendLine()
+    appendLine("    /// Length of the `callback_data` of a value encoded in `size` bytes of `postcard` after `prefix`.")
+    appendLine("    pub const fn callback_data_length(prefix: &str, size: usize) -> usize {")
+    appendLine("        prefix.len() + (size * 4).div_ceil(3)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    // Separates the prefix from the key of data kept by a CallbackDataStore, it's not a base64url character")
+    appendLine("    const STORE_MARKER: char = '~';")
+    appendLine()
+    appendLine("    /// Error of the [CallbackData] codec.")
+    appendLine("    #[derive(Clone, PartialEq, Eq, Hash, Debug)]")
+    appendLine("    pub enum CallbackDataError {")
+    appendLine("        /// The encoded value is longer than [CALLBACK_DATA_MAX_LENGTH], use a [CallbackDataStore] or fewer fields.")
+    appendLine("        TooLong { length: usize },")
+    appendLine("        /// The data doesn't start with the prefix of the type, e.g. a button of another version of the bot.")
+    appendLine("        Prefix { expected: &'static str },")
+    appendLine("        /// The callback query has no `data`.")
+    appendLine("        Missing,")
+    appendLine("        /// The key is unknown to the [CallbackDataStore], e.g. after a restart with an in-memory store.")
+    appendLine("        Expired { key: String },")
+    appendLine("        /// The data is not base64url or doesn't decode to the type.")
+    appendLine("        Invalid(String),")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl std::fmt::Display for CallbackDataError {")
+    appendLine("        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")
+    appendLine("            match self {")
+    appendLine("                Self::TooLong { length } => write!(f, \"callback data is {} bytes, the limit is {}\", length, CALLBACK_DATA_MAX_LENGTH),")
+    appendLine("                Self::Prefix { expected } => write!(f, \"callback data doesn't start with {:?}\", expected),")
+    appendLine("                Self::Missing => write!(f, \"callback query without data\"),")
+    appendLine("                Self::Expired { key } => write!(f, \"callback data {:?} not found in the store\", key),")
+    appendLine("                Self::Invalid(message) => write!(f, \"invalid callback data: {}\", message),")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl std::error::Error for CallbackDataError {}")
+    appendLine()
+    appendLine("    /**")
+    appendLine("     * A value sent as `callback_data`: the [CallbackData::PREFIX] followed by the value encoded with `postcard`")
+    appendLine("     * in base64url, within [CALLBACK_DATA_MAX_LENGTH].")
+    appendLine("     *")
+    appendLine("     * ```ignore")
+    appendLine("     * #[derive(Serialize, Deserialize)]")
+    appendLine("     * struct Vote { poll: u32, option: u8 }")
+    appendLine("     *")
+    appendLine("     * impl CallbackData for Vote {")
+    appendLine("     *     const PREFIX: &'static str = \"vote1:\";")
+    appendLine("     * }")
+    appendLine("     *")
+    appendLine("     * let button = InlineKeyboardButton::new_callback(\"Yes\", &Vote { poll: 7, option: 0 })?;")
+    appendLine("     * let vote: Vote = callback_query.parse_data()?;")
+    appendLine("     * ```")
+    appendLine("     *")
+    appendLine("     * A type with a bounded encoding declares it in [CallbackData::MAX_ENCODED_LEN], e.g. 5 + 1 bytes for `Vote`,")
+    appendLine("     * and a build using it fails when it can't fit instead of [CallbackDataError::TooLong] at runtime.")
+    appendLine("     * */")
+    appendLine("    pub trait CallbackData: Serialize + serde::de::DeserializeOwned {")
+    appendLine("        /// Identifies the type and its version, e.g. `vote1:`. A new prefix rejects the buttons sent with the old fields.")
+    appendLine("        const PREFIX: &'static str;")
+    appendLine()
+    appendLine("        /**")
+    appendLine("         * Maximum length of the `postcard` encoding of the type in bytes, `None` when unbounded, e.g. with a `String`.")
+    appendLine("         * An integer takes up to 1 byte per 7 bits, e.g. 5 bytes for a `u32`, and an enum variant 1 byte.")
+    appendLine("         * */")
+    appendLine("        const MAX_ENCODED_LEN: Option<usize> = None;")
+    appendLine()
+    appendLine("        /// Fails to build when [CallbackData::MAX_ENCODED_LEN] can't fit in [CALLBACK_DATA_MAX_LENGTH], evaluated by the encoding functions or e.g. `const _: () = Vote::ASSERT_FITS;`.")
+    appendLine("        const ASSERT_FITS: () = if let Some(size) = Self::MAX_ENCODED_LEN {")
+    appendLine("            assert!(callback_data_length(Self::PREFIX, size) <= CALLBACK_DATA_MAX_LENGTH, \"CallbackData::MAX_ENCODED_LEN doesn't fit in callback_data\");")
+    appendLine("        };")
+    appendLine()
+    appendLine("        /// Encodes `self`, failing when it's longer than [CALLBACK_DATA_MAX_LENGTH].")
+    appendLine("        fn to_callback_data(&self) -> Result<String, CallbackDataError> {")
+    appendLine("            let () = Self::ASSERT_FITS;")
+    appendLine("            checked_length(encode(self)?)")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// Decodes data written by [CallbackData::to_callback_data].")
+    appendLine("        fn from_callback_data(data: &str) -> Result<Self, CallbackDataError> {")
+    appendLine("            decode(data.strip_prefix(Self::PREFIX).ok_or(CallbackDataError::Prefix { expected: Self::PREFIX })?)")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// Encodes `self`, keeping it in `store` behind a short key when it's longer than [CALLBACK_DATA_MAX_LENGTH].")
+    appendLine("        fn to_callback_data_with(&self, store: &impl CallbackDataStore) -> Result<String, CallbackDataError> {")
+    appendLine("            let () = Self::ASSERT_FITS;")
+    appendLine("            let data = encode(self)?;")
+    appendLine("            if data.len() <= CALLBACK_DATA_MAX_LENGTH {")
+    appendLine("                return Ok(data);")
+    appendLine("            }")
+    appendLine("            let key = store.insert(data[Self::PREFIX.len()..].to_string());")
+    appendLine("            checked_length(format!(\"{}{}{}\", Self::PREFIX, STORE_MARKER, key))")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// Decodes data written by [CallbackData::to_callback_data_with].")
+    appendLine("        fn from_callback_data_with(data: &str, store: &impl CallbackDataStore) -> Result<Self, CallbackDataError> {")
+    appendLine("            let data = data.strip_prefix(Self::PREFIX).ok_or(CallbackDataError::Prefix { expected: Self::PREFIX })?;")
+    appendLine("            match data.strip_prefix(STORE_MARKER) {")
+    appendLine("                Some(key) => decode(&store.get(key).ok_or_else(|| CallbackDataError::Expired { key: key.to_string() })?),")
+    appendLine("                None => decode(data),")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn encode<T: CallbackData>(value: &T) -> Result<String, CallbackDataError> {")
+    appendLine("        let bytes = postcard::to_allocvec(value).map_err(|e| CallbackDataError::Invalid(e.to_string()))?;")
+    appendLine("        Ok(format!(\"{}{}\", T::PREFIX, base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, bytes)))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn decode<T: CallbackData>(data: &str) -> Result<T, CallbackDataError> {")
+    appendLine("        let bytes = base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, data)")
+    appendLine("            .map_err(|e| CallbackDataError::Invalid(e.to_string()))?;")
+    appendLine("        postcard::from_bytes(&bytes).map_err(|e| CallbackDataError::Invalid(e.to_string()))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn checked_length(data: String) -> Result<String, CallbackDataError> {")
+    appendLine("        if data.len() > CALLBACK_DATA_MAX_LENGTH {")
+    appendLine("            return Err(CallbackDataError::TooLong { length: data.len() });")
+    appendLine("        }")
+    appendLine("        Ok(data)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Server-side storage of the callback data too long for a button, see [CallbackData::to_callback_data_with].")
+    appendLine("    pub trait CallbackDataStore {")
+    appendLine("        /// Keeps `data`, returning a short key to find it.")
+    appendLine("        fn insert(&self, data: String) -> String;")
+    appendLine()
+    appendLine("        /// The data kept with `key`.")
+    appendLine("        fn get(&self, key: &str) -> Option<String>;")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /**")
+    appendLine("     * A [CallbackDataStore] in memory: the buttons using it stop working when the bot restarts.")
+    appendLine("     *")
+    appendLine("     * The key is a hash of the data, so the same data is kept once. Beyond the capacity, the oldest data is evicted")
+    appendLine("     * and its buttons fail with [CallbackDataError::Expired].")
+    appendLine("     * */")
+    appendLine("    #[derive(Debug)]")
+    appendLine("    pub struct MemoryCallbackDataStore {")
+    appendLine("        capacity: usize,")
+    appendLine("        data: std::sync::Mutex<MemoryCallbackData>,")
+    appendLine("    }")
+    appendLine()
+    appendLine("    #[derive(Debug, Default)]")
+    appendLine("    struct MemoryCallbackData {")
+    appendLine("        values: std::collections::HashMap<String, String>,")
+    appendLine("        // keys from the oldest to the newest")
+    appendLine("        order: std::collections::VecDeque<String>,")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl Default for MemoryCallbackDataStore {")
+    appendLine("        fn default() -> Self {")
+    appendLine("            Self::with_capacity(10_000)")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl MemoryCallbackDataStore {")
+    appendLine("        /// A store keeping the latest 10 000 data.")
+    appendLine("        pub fn new() -> Self {")
+    appendLine("            Self::default()")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// A store keeping the latest `capacity` data.")
+    appendLine("        pub fn with_capacity(capacity: usize) -> Self {")
+    appendLine("            Self { capacity: capacity.max(1), data: Default::default() }")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// Forgets the data kept with `key`, e.g. once its message is deleted.")
+    appendLine("        pub fn remove(&self, key: &str) -> Option<String> {")
+    appendLine("            let mut data = self.data.lock().unwrap_or_else(std::sync::PoisonError::into_inner);")
+    appendLine("            data.order.retain(|k| k != key);")
+    appendLine("            data.values.remove(key)")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// Number of data kept.")
+    appendLine("        pub fn len(&self) -> usize {")
+    appendLine("            self.data.lock().unwrap_or_else(std::sync::PoisonError::into_inner).values.len()")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// No data is kept.")
+    appendLine("        pub fn is_empty(&self) -> bool {")
+    appendLine("            self.len() == 0")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl CallbackDataStore for MemoryCallbackDataStore {")
+    appendLine("        fn insert(&self, value: String) -> String {")
+    appendLine("            use std::hash::{Hash, Hasher};")
+    appendLine("            let mut hasher = std::collections::hash_map::DefaultHasher::new();")
+    appendLine("            value.hash(&mut hasher);")
+    appendLine("            let mut hash = hasher.finish();")
+    appendLine("            let mut data = self.data.lock().unwrap_or_else(std::sync::PoisonError::into_inner);")
+    appendLine("            // the next hash on a collision with other data")
+    appendLine("            let key = loop {")
+    appendLine("                let key = format!(\"{:x}\", hash);")
+    appendLine("                match data.values.get(&key) {")
+    appendLine("                    Some(kept) if *kept != value => hash = hash.wrapping_add(1),")
+    appendLine("                    _ => break key,")
+    appendLine("                }")
+    appendLine("            };")
+    appendLine("            data.order.retain(|k| *k != key);")
+    appendLine("            data.order.push_back(key.clone());")
+    appendLine("            data.values.insert(key.clone(), value);")
+    appendLine("            while data.order.len() > self.capacity {")
+    appendLine("                if let Some(oldest) = data.order.pop_front() {")
+    appendLine("                    data.values.remove(&oldest);")
+    appendLine("                }")
+    appendLine("            }")
+    appendLine("            key")
+    appendLine("        }")
+    appendLine()
+    appendLine("        fn get(&self, key: &str) -> Option<String> {")
+    appendLine("            self.data.lock().unwrap_or_else(std::sync::PoisonError::into_inner).values.get(key).cloned()")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl InlineKeyboardButton {")
+    appendLine("        /// Creates a [InlineKeyboardButton] sending `value` as `callback_data`, see [CallbackData::to_callback_data].")
+    appendLine("        pub fn new_callback(text: impl Into<String>, value: &impl CallbackData) -> Result<Self, CallbackDataError> {")
+    appendLine("            Ok(Self::new_callback_data(text, value.to_callback_data()?))")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    impl CallbackQuery {")
+    appendLine("        /// `data` decoded as a `T`, see [CallbackData::from_callback_data].")
+    appendLine("        pub fn parse_data<T: CallbackData>(&self) -> Result<T, CallbackDataError> {")
+    appendLine("            T::from_callback_data(self.data.as_deref().ok_or(CallbackDataError::Missing)?)")
+    appendLine("        }")
+    appendLine()
+    appendLine("        /// `data` decoded as a `T`, see [CallbackData::from_callback_data_with].")
+    appendLine("        pub fn parse_data_with<T: CallbackData>(&self, store: &impl CallbackDataStore) -> Result<T, CallbackDataError> {")
+    appendLine("            T::from_callback_data_with(self.data.as_deref().ok_or(CallbackDataError::Missing)?, store)")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+}
@@ -0,0 +1,415 @@
+/**
+ * Commands sent to the bot: `Message::command` reads the `bot_command` entity at the start of a message,
+ * and `BotCommands` (usually implemented with the `bot_commands!` macro) turns a Rust enum into a command parser
+ * and the `BotCommand` list of `setMyCommands`. `CommandRegistry` declares the commands once per scope and language
+ * and syncs them with `getMyCommands`, `setMyCommands` and `deleteMyCommands`.
+ */
+fun List<DocSection>.toRustCommands() = buildString {
+    append(rustCommands())
+    appendLine()
+    append(rustCommandRegistry())
+}
+
+private fun rustCommands() = buildString {
+    appendLine("/// A command at the start of a message, e.g. `/start@my_bot payload`, see [Message::command].")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct Command<'a> {")
+    appendLine("    /// Name without the `/`, e.g. `start`.")
+    appendLine("    pub name: &'a str,")
+    appendLine("    /// Username after the `@`, e.g. `my_bot`, when the command names the bot.")
+    appendLine("    pub bot_username: Option<&'a str>,")
+    appendLine("    /// Rest of the text, trimmed, e.g. `payload`.")
+    appendLine("    pub args: &'a str,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<'a> Command<'a> {")
+    appendLine("    /**")
+    appendLine("     * The `bot_command` entity at offset 0 of `text`, with the text after it as `args`.")
+    appendLine("     * A command naming another bot than `bot_username` is ignored, usernames are compared ignoring case.")
+    appendLine("     * */")
+    appendLine("    pub fn parse(text: &'a str, entities: &[MessageEntity], bot_username: Option<&str>) -> Option<Self> {")
+    appendLine("        let entity = entities.iter().find(|entity| entity.offset == 0 && entity.kind() == EntityKind::BotCommand)?;")
+    appendLine("        let range = entity.byte_range(text)?;")
+    appendLine("        let command = text[range.clone()].strip_prefix('/')?;")
+    appendLine("        let (name, mention) = command.split_once('@').map_or((command, None), |(name, mention)| (name, Some(mention)));")
+    appendLine("        if mention.is_some_and(|mention| !bot_username.is_some_and(|username| username.eq_ignore_ascii_case(mention))) {")
+    appendLine("            return None;")
+    appendLine("        }")
+    appendLine("        Some(Self { name, bot_username: mention, args: text[range.end..].trim() })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// `args` split on whitespace.")
+    appendLine("    pub fn split_args(&self) -> impl Iterator<Item = &'a str> {")
+    appendLine("        self.args.split_whitespace()")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Message {")
+    appendLine("    /**")
+    appendLine("     * The command at the start of `text` (or `caption`), `bot` is the user returned by `getMe`.")
+    appendLine("     * Returns None for a command naming another bot, e.g. `/start@other_bot` in a group.")
+    appendLine("     * */")
+    appendLine("    pub fn command(&self, bot: &User) -> Option<Command<'_>> {")
+    appendLine("        let username = bot.username.as_deref();")
+    appendLine("        let text = self.text.as_deref().map(|text| (text, self.entities.as_deref()));")
+    appendLine("        let caption = self.caption.as_deref().map(|caption| (caption, self.caption_entities.as_deref()));")
+    appendLine("        let (text, entities) = text.or(caption)?;")
+    appendLine("        Command::parse(text, entities.unwrap_or_default(), username)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Error of [BotCommands::parse].")
+    appendLine("#[derive(Clone, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub enum CommandError {")
+    appendLine("    /// The name is not one of the commands.")
+    appendLine("    Unknown { name: String },")
+    appendLine("    /// An argument is missing or can't be parsed.")
+    appendLine("    InvalidArgument { name: String, message: String },")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::fmt::Display for CommandError {")
+    appendLine("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")
+    appendLine("        match self {")
+    appendLine("            Self::Unknown { name } => write!(f, \"unknown command /{}\", name),")
+    appendLine("            Self::InvalidArgument { name, message } => write!(f, \"invalid argument of /{}: {}\", name, message),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::error::Error for CommandError {}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Commands of a bot as a Rust enum, usually implemented with [bot_commands!]: parsed from a [Command],")
+    appendLine(" * and listed with their descriptions for [SetMyCommandsRequest].")
+    appendLine(" * */")
+    appendLine("pub trait BotCommands: Sized {")
+    appendLine("    /// Parses a command sent to the bot.")
+    appendLine("    fn parse(command: &Command<'_>) -> Result<Self, CommandError>;")
+    appendLine()
+    appendLine("    /// Every command with its description, as sent with `setMyCommands`.")
+    appendLine("    fn bot_commands() -> Vec<BotCommand>;")
+    appendLine()
+    appendLine("    /// The command of `message`, None when it has none or names another bot, see [Message::command].")
+    appendLine("    fn from_message(message: &Message, bot: &User) -> Option<Result<Self, CommandError>> {")
+    appendLine("        message.command(bot).map(|command| Self::parse(&command))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// A `setMyCommands` request with [BotCommands::bot_commands].")
+    appendLine("    fn set_my_commands_request() -> SetMyCommandsRequest {")
+    appendLine("        SetMyCommandsRequest::new(Self::bot_commands())")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Arguments of a [Command] read one by one, the last one takes the rest of `args`, see [bot_commands!].")
+    appendLine("#[derive(Clone, Debug)]")
+    appendLine("pub struct CommandArgs<'a> {")
+    appendLine("    name: &'a str,")
+    appendLine("    rest: &'a str,")
+    appendLine("    remaining: usize,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<'a> CommandArgs<'a> {")
+    appendLine("    /// Reads `count` arguments of `command`.")
+    appendLine("    pub fn new(command: &Command<'a>, count: usize) -> Self {")
+    appendLine("        Self { name: command.name, rest: command.args, remaining: count }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Parses the next argument, an empty one when `args` has no more words.")
+    appendLine("    pub fn parse_next<T: std::str::FromStr>(&mut self) -> Result<T, CommandError>")
+    appendLine("    where")
+    appendLine("        T::Err: std::fmt::Display,")
+    appendLine("    {")
+    appendLine("        self.remaining = self.remaining.saturating_sub(1);")
+    appendLine("        let rest = self.rest.trim_start();")
+    appendLine("        let (value, rest) = if self.remaining == 0 {")
+    appendLine("            (rest.trim_end(), \"\")")
+    appendLine("        } else {")
+    appendLine("            rest.split_once(char::is_whitespace).unwrap_or((rest, \"\"))")
+    appendLine("        };")
+    appendLine("        self.rest = rest;")
+    appendLine("        value.parse().map_err(|e: T::Err| CommandError::InvalidArgument { name: self.name.to_string(), message: e.to_string() })")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Command name of a variant, e.g. `SetName` -> `set_name`.")
+    appendLine("pub fn command_name(variant: &str) -> String {")
+    appendLine("    let mut name = String::new();")
+    appendLine("    for (index, c) in variant.char_indices() {")
+    appendLine("        if c.is_uppercase() && index > 0 {")
+    appendLine("            name.push('_');")
+    appendLine("        }")
+    appendLine("        name.extend(c.to_lowercase());")
+    appendLine("    }")
+    appendLine("    name")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Declares an enum of commands implementing [BotCommands]. Variant names become snake_case command names and")
+    appendLine(" * their doc comments the descriptions. Fields implement [std::str::FromStr], they are read from the words of")
+    appendLine(" * `args`, the last one takes the rest of the text.")
+    appendLine(" *")
+    appendLine(" * The expansion names the models with `\$crate::`, they must be at the root of the crate, e.g. `include!`d in")
+    appendLine(" * `lib.rs` or re-exported with `pub use models::*;`.")
+    appendLine(" *")
+    appendLine(" * ```ignore")
+    appendLine(" * bot_commands! {")
+    appendLine(" *     #[derive(Clone, Debug)]")
+    appendLine(" *     pub enum Commands {")
+    appendLine(" *         /// Start the bot")
+    appendLine(" *         Start,")
+    appendLine(" *         /// Roll a dice with the given number of sides")
+    appendLine(" *         Roll(u8),")
+    appendLine(" *         /// Rename the group")
+    appendLine(" *         SetName(String),")
+    appendLine(" *     }")
+    appendLine(" * }")
+    appendLine(" * ```")
+    appendLine(" * */")
+    appendLine("#[macro_export]")
+    appendLine("macro_rules! bot_commands {")
+    appendLine("    (")
+    appendLine("        \$(#[\$meta:meta])*")
+    appendLine("        \$vis:vis enum \$name:ident {")
+    appendLine("            \$(")
+    appendLine("                \$(#[doc = \$doc:literal])*")
+    appendLine("                \$variant:ident \$((\$(\$field:ty),+ \$(,)?))?")
+    appendLine("            ),* \$(,)?")
+    appendLine("        }")
+    appendLine("    ) => {")
+    appendLine("        \$(#[\$meta])*")
+    appendLine("        \$vis enum \$name {")
+    appendLine("            \$(")
+    appendLine("                \$(#[doc = \$doc])*")
+    appendLine("                \$variant \$((\$(\$field),+))?")
+    appendLine("            ),*")
+    appendLine("        }")
+    appendLine()
+    appendLine("        impl \$crate::BotCommands for \$name {")
+    appendLine("            fn parse(command: &\$crate::Command<'_>) -> ::std::result::Result<Self, \$crate::CommandError> {")
+    appendLine("                \$(")
+    appendLine("                    if command.name.eq_ignore_ascii_case(&\$crate::command_name(::std::stringify!(\$variant))) {")
+    appendLine("                        #[allow(unused_mut, unused_variables)]")
+    appendLine("                        let mut args = \$crate::CommandArgs::new(command, <[&str]>::len(&[\$(\$(::std::stringify!(\$field)),+)?]));")
+    appendLine("                        return ::std::result::Result::Ok(Self::\$variant \$((\$(args.parse_next::<\$field>()?),+))?);")
+    appendLine("                    }")
+    appendLine("                )*")
+    appendLine("                ::std::result::Result::Err(\$crate::CommandError::Unknown { name: ::std::string::ToString::to_string(command.name) })")
+    appendLine("            }")
+    appendLine()
+    appendLine("            fn bot_commands() -> ::std::vec::Vec<\$crate::BotCommand> {")
+    appendLine("                ::std::vec![\$({")
+    appendLine("                    let name = \$crate::command_name(::std::stringify!(\$variant));")
+    appendLine("                    let description = <[&str]>::join(&[\$(\$doc.trim()),*], \" \");")
+    appendLine("                    \$crate::BotCommand::new(::std::clone::Clone::clone(&name), if description.is_empty() { name } else { description })")
+    appendLine("                }),*]")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("    };")
+    appendLine("}")
+}
+
+private fun rustCommandRegistry() = buildString {
+    appendLine("/// A command of a [CommandRegistry], with the scopes showing it and its translated descriptions.")
+    appendLine("#[derive(Clone, PartialEq, Eq, Debug)]")
+    appendLine("pub struct RegisteredCommand {")
+    appendLine("    /// Name without the `/`, e.g. `start`.")
+    appendLine("    pub command: String,")
+    appendLine("    /// Description for the languages without a translation.")
+    appendLine("    pub description: String,")
+    appendLine("    /// Descriptions by IETF language code, e.g. `(\"it\", \"Avvia il bot\")`.")
+    appendLine("    pub translations: Vec<(String, String)>,")
+    appendLine("    /// Scopes showing the command, the default scope when empty.")
+    appendLine("    pub scopes: Vec<BotCommandScope>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl RegisteredCommand {")
+    appendLine("    /// Creates a command shown in the default scope, until [RegisteredCommand::scope] is called.")
+    appendLine("    pub fn new(command: impl Into<String>, description: impl Into<String>) -> Self {")
+    appendLine("        Self { command: command.into(), description: description.into(), translations: Vec::new(), scopes: Vec::new() }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Shows the command in `scope` too.")
+    appendLine("    pub fn scope(mut self, scope: impl Into<BotCommandScope>) -> Self {")
+    appendLine("        self.scopes.push(scope.into());")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Sets the description for `language_code`, e.g. \"it\".")
+    appendLine("    pub fn translation(mut self, language_code: impl Into<String>, description: impl Into<String>) -> Self {")
+    appendLine("        self.translations.push((language_code.into(), description.into()));")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn scopes(&self) -> Vec<BotCommandScope> {")
+    appendLine("        if self.scopes.is_empty() { vec![BotCommandScopeDefault::new().into()] } else { self.scopes.clone() }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn description_in(&self, language_code: Option<&str>) -> Option<&str> {")
+    appendLine("        match language_code {")
+    appendLine("            None => Some(&self.description),")
+    appendLine("            Some(code) => self.translations.iter().find(|(language, _)| language == code).map(|(_, description)| description.as_str()),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<BotCommand> for RegisteredCommand {")
+    appendLine("    fn from(command: BotCommand) -> Self {")
+    appendLine("        Self::new(command.command, command.description)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// The commands of a scope and language, as set with `setMyCommands`.")
+    appendLine("#[derive(Clone, PartialEq, Eq, Debug)]")
+    appendLine("pub struct CommandList {")
+    appendLine("    pub scope: BotCommandScope,")
+    appendLine("    /// None for the users without a dedicated list.")
+    appendLine("    pub language_code: Option<String>,")
+    appendLine("    pub commands: Vec<BotCommand>,")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A call made by [CommandRegistry::sync].")
+    appendLine("#[derive(Clone, PartialEq, Eq, Debug)]")
+    appendLine("pub enum CommandUpdate {")
+    appendLine("    Set(SetMyCommandsRequest),")
+    appendLine("    Delete(DeleteMyCommandsRequest),")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Commands declared once, with the scopes showing them and their translations, see [CommandRegistry::sync].")
+    appendLine(" *")
+    appendLine(" * Telegram shows the list of the narrowest scope having one, e.g. a `chat_administrators` list replaces the")
+    appendLine(" * `default` one for the administrators: a command is declared in every scope showing it.")
+    appendLine(" * */")
+    appendLine("#[derive(Clone, PartialEq, Eq, Debug, Default)]")
+    appendLine("pub struct CommandRegistry {")
+    appendLine("    commands: Vec<RegisteredCommand>,")
+    appendLine("    scopes: Vec<BotCommandScope>,")
+    appendLine("    languages: Vec<String>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl CommandRegistry {")
+    appendLine("    pub fn new() -> Self {")
+    appendLine("        Self::default()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds a command, e.g. `RegisteredCommand::new(\"ban\", \"Ban a user\").scope(BotCommandScopeAllChatAdministrators::new())`.")
+    appendLine("    pub fn command(mut self, command: impl Into<RegisteredCommand>) -> Self {")
+    appendLine("        self.commands.push(command.into());")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds the commands of `C`, see [BotCommands], shown in `scopes` (the default scope when empty).")
+    appendLine("    pub fn bot_commands<C: BotCommands>(mut self, scopes: impl IntoIterator<Item = impl Into<BotCommandScope>>) -> Self {")
+    appendLine("        let scopes: Vec<BotCommandScope> = scopes.into_iter().map(Into::into).collect();")
+    appendLine("        for command in C::bot_commands() {")
+    appendLine("            let mut command = RegisteredCommand::from(command);")
+    appendLine("            command.scopes.clone_from(&scopes);")
+    appendLine("            self.commands.push(command);")
+    appendLine("        }")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Keeps `scope` in sync even without commands, so that its list is deleted once no command uses it.")
+    appendLine("    pub fn scope(mut self, scope: impl Into<BotCommandScope>) -> Self {")
+    appendLine("        self.scopes.push(scope.into());")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Keeps `language_code` in sync even without translations, so that its lists are deleted once no command uses it.")
+    appendLine("    pub fn language(mut self, language_code: impl Into<String>) -> Self {")
+    appendLine("        self.languages.push(language_code.into());")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /**")
+    appendLine("     * The list of every scope and language: a language has a list in a scope when a command of the scope is")
+    appendLine("     * translated in it, the commands without a translation keep their description.")
+    appendLine("     * */")
+    appendLine("    pub fn lists(&self) -> Vec<CommandList> {")
+    appendLine("        let mut scopes = self.scopes.clone();")
+    appendLine("        let mut languages = self.languages.clone();")
+    appendLine("        for command in &self.commands {")
+    appendLine("            for scope in command.scopes() {")
+    appendLine("                if !scopes.contains(&scope) {")
+    appendLine("                    scopes.push(scope);")
+    appendLine("                }")
+    appendLine("            }")
+    appendLine("            for (language, _) in &command.translations {")
+    appendLine("                if !languages.contains(language) {")
+    appendLine("                    languages.push(language.clone());")
+    appendLine("                }")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("        let mut lists = Vec::new();")
+    appendLine("        for scope in scopes {")
+    appendLine("            let commands: Vec<_> = self.commands.iter().filter(|command| command.scopes().contains(&scope)).collect();")
+    appendLine("            for language_code in std::iter::once(None).chain(languages.iter().map(|language| Some(language.as_str()))) {")
+    appendLine("                let translated = commands.iter().any(|command| command.description_in(language_code).is_some());")
+    appendLine("                lists.push(CommandList {")
+    appendLine("                    scope: scope.clone(),")
+    appendLine("                    language_code: language_code.map(str::to_string),")
+    appendLine("                    commands: if translated {")
+    appendLine("                        commands")
+    appendLine("                            .iter()")
+    appendLine("                            .map(|command| {")
+    appendLine("                                let description = command.description_in(language_code).unwrap_or(&command.description);")
+    appendLine("                                BotCommand::new(command.command.clone(), description)")
+    appendLine("                            })")
+    appendLine("                            .collect()")
+    appendLine("                    } else {")
+    appendLine("                        Vec::new()")
+    appendLine("                    },")
+    appendLine("                });")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("        lists")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The calls turning the `current` lists (from `getMyCommands`) into [CommandRegistry::lists], in the same order.")
+    appendLine("    pub fn updates(&self, mut current: impl FnMut(&BotCommandScope, Option<&str>) -> Vec<BotCommand>) -> Vec<CommandUpdate> {")
+    appendLine("        self.lists()")
+    appendLine("            .into_iter()")
+    appendLine("            .filter_map(|list| {")
+    appendLine("                let commands = current(&list.scope, list.language_code.as_deref());")
+    appendLine("                list.update(commands)")
+    appendLine("            })")
+    appendLine("            .collect()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Reads every list with `getMyCommands` and calls `setMyCommands` or `deleteMyCommands` only where it differs, returning the calls made.")
+    appendLine("    pub async fn sync<C: TelegramClient>(&self, client: &C) -> Result<Vec<CommandUpdate>, C::Error> {")
+    appendLine("        let mut updates = Vec::new();")
+    appendLine("        for list in self.lists() {")
+    appendLine("            let mut request = GetMyCommandsRequest::new().scope(list.scope.clone());")
+    appendLine("            request.language_code.clone_from(&list.language_code);")
+    appendLine("            let current = client.call(&request).await?;")
+    appendLine("            if let Some(update) = list.update(current) {")
+    appendLine("                match &update {")
+    appendLine("                    CommandUpdate::Set(request) => client.call(request).await?,")
+    appendLine("                    CommandUpdate::Delete(request) => client.call(request).await?,")
+    appendLine("                };")
+    appendLine("                updates.push(update);")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("        Ok(updates)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl CommandList {")
+    appendLine("    // The call replacing `current` with this list, None when they are equal")
+    appendLine("    fn update(self, current: Vec<BotCommand>) -> Option<CommandUpdate> {")
+    appendLine("        if current == self.commands {")
+    appendLine("            return None;")
+    appendLine("        }")
+    appendLine("        Some(if self.commands.is_empty() {")
+    appendLine("            let mut request = DeleteMyCommandsRequest::new().scope(self.scope);")
+    appendLine("            request.language_code = self.language_code;")
+    appendLine("            CommandUpdate::Delete(request)")
+    appendLine("        } else {")
+    appendLine("            let mut request = SetMyCommandsRequest::new(self.commands).scope(self.scope);")
+    appendLine("            request.language_code = self.language_code;")
+    appendLine("            CommandUpdate::Set(request)")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine("}")
+}
@@ -0,0 +1,263 @@
+/**
+ * Conversations in several steps: `Dialogues` keeps a typed state per chat, user and forum topic (`DialogueKey`)
+ * in a `Storage`, in memory (`MemoryStorage`) or in a JSON file surviving restarts (`JsonFileStorage`),
+ * with a timeout, cancel commands and `Filter`s routing the updates of the `Dispatcher` by state.
+ */
+fun List<DocSection>.toRustDialogue() = buildString {
+    append(rustDialogue())
+}
+
+private fun rustDialogue() = buildString {
+    appendLine("/// Key of a conversation: the chat, the user and the forum topic, see [DialogueKey::from_update].")
+    appendLine("#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct DialogueKey {")
+    appendLine("    pub chat_id: Integer,")
+    appendLine("    pub user_id: Integer,")
+    appendLine("    /// `message_thread_id` of a message in a forum topic.")
+    appendLine("    pub thread_id: Option<Integer>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl DialogueKey {")
+    appendLine("    /// The key of the chat and user of `update`, with the topic of its message or of the message of its callback query.")
+    appendLine("    pub fn from_update(update: &Update) -> Option<Self> {")
+    appendLine("        let message = update")
+    appendLine("            .effective_message()")
+    appendLine("            .or_else(|| update.callback_query.as_ref()?.message.as_ref()?.message());")
+    appendLine("        let thread_id = message.filter(|message| message.is_topic_message == Some(true)).and_then(|message| message.message_thread_id);")
+    appendLine("        Some(Self { chat_id: update.effective_chat()?.id, user_id: update.effective_user()?.id, thread_id })")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A state saved by a [Storage], with the time it was saved to expire it.")
+    appendLine("#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct StoredState<S> {")
+    appendLine("    pub state: S,")
+    appendLine("    /// Unix time of the last change, in milliseconds.")
+    appendLine("    pub updated: u64,")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Keeps the state of the conversations, see [MemoryStorage] and [JsonFileStorage].")
+    appendLine("pub trait Storage<S>: Send + Sync {")
+    appendLine("    type Error: std::error::Error + Send + Sync + 'static;")
+    appendLine()
+    appendLine("    fn get(&self, key: &DialogueKey) -> Result<Option<StoredState<S>>, Self::Error>;")
+    appendLine()
+    appendLine("    fn set(&self, key: &DialogueKey, state: StoredState<S>) -> Result<(), Self::Error>;")
+    appendLine()
+    appendLine("    fn remove(&self, key: &DialogueKey) -> Result<(), Self::Error>;")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A [Storage] in memory, the conversations end when the bot restarts.")
+    appendLine("#[derive(Debug)]")
+    appendLine("pub struct MemoryStorage<S> {")
+    appendLine("    states: std::sync::Mutex<std::collections::HashMap<DialogueKey, StoredState<S>>>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S> Default for MemoryStorage<S> {")
+    appendLine("    fn default() -> Self {")
+    appendLine("        Self { states: std::sync::Mutex::new(std::collections::HashMap::new()) }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S> MemoryStorage<S> {")
+    appendLine("    pub fn new() -> Self {")
+    appendLine("        Self::default()")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S: Clone + Send> Storage<S> for MemoryStorage<S> {")
+    appendLine("    type Error = std::convert::Infallible;")
+    appendLine()
+    appendLine("    fn get(&self, key: &DialogueKey) -> Result<Option<StoredState<S>>, Self::Error> {")
+    appendLine("        Ok(self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner).get(key).cloned())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn set(&self, key: &DialogueKey, state: StoredState<S>) -> Result<(), Self::Error> {")
+    appendLine("        self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner).insert(*key, state);")
+    appendLine("        Ok(())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn remove(&self, key: &DialogueKey) -> Result<(), Self::Error> {")
+    appendLine("        self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner).remove(key);")
+    appendLine("        Ok(())")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * A [Storage] in a JSON file, rewritten on every change, so that the conversations survive a restart.")
+    appendLine(" * The states are serialized with serde, a state that can't be read back fails [JsonFileStorage::open].")
+    appendLine(" * */")
+    appendLine("#[derive(Debug)]")
+    appendLine("pub struct JsonFileStorage<S> {")
+    appendLine("    path: std::path::PathBuf,")
+    appendLine("    states: std::sync::Mutex<std::collections::HashMap<DialogueKey, StoredState<S>>>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S: Serialize + serde::de::DeserializeOwned> JsonFileStorage<S> {")
+    appendLine("    /// Reads the states saved in `path`, none when the file doesn't exist yet.")
+    appendLine("    pub fn open(path: impl Into<std::path::PathBuf>) -> std::io::Result<Self> {")
+    appendLine("        let path = path.into();")
+    appendLine("        let states: Vec<(DialogueKey, StoredState<S>)> = match std::fs::read(&path) {")
+    appendLine("            Ok(json) => serde_json::from_slice(&json)?,")
+    appendLine("            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),")
+    appendLine("            Err(e) => return Err(e),")
+    appendLine("        };")
+    appendLine("        Ok(Self { path, states: std::sync::Mutex::new(states.into_iter().collect()) })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    // Writes a temporary file renamed over the previous one, a crash never leaves half a file")
+    appendLine("    fn save(&self, states: &std::collections::HashMap<DialogueKey, StoredState<S>>) -> std::io::Result<()> {")
+    appendLine("        let json = serde_json::to_vec(&states.iter().collect::<Vec<_>>())?;")
+    appendLine("        let mut temporary = self.path.clone().into_os_string();")
+    appendLine("        temporary.push(\".tmp\");")
+    appendLine("        std::fs::write(&temporary, json)?;")
+    appendLine("        std::fs::rename(&temporary, &self.path)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S: Serialize + serde::de::DeserializeOwned + Clone + Send> Storage<S> for JsonFileStorage<S> {")
+    appendLine("    type Error = std::io::Error;")
+    appendLine()
+    appendLine("    fn get(&self, key: &DialogueKey) -> Result<Option<StoredState<S>>, Self::Error> {")
+    appendLine("        Ok(self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner).get(key).cloned())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn set(&self, key: &DialogueKey, state: StoredState<S>) -> Result<(), Self::Error> {")
+    appendLine("        let mut states = self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner);")
+    appendLine("        states.insert(*key, state);")
+    appendLine("        self.save(&states)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn remove(&self, key: &DialogueKey) -> Result<(), Self::Error> {")
+    appendLine("        let mut states = self.states.lock().unwrap_or_else(std::sync::PoisonError::into_inner);")
+    appendLine("        if states.remove(key).is_some() {")
+    appendLine("            self.save(&states)?;")
+    appendLine("        }")
+    appendLine("        Ok(())")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Conversations in several steps, with a typed state per [DialogueKey] saved in a [Storage].")
+    appendLine(" * A conversation ends when a handler calls [Dialogues::exit], after the timeout without changes,")
+    appendLine(" * or when the handler of [Dialogues::cancel_filter] exits it, e.g. [Dialogues::cancel].")
+    appendLine(" *")
+    appendLine(" * ```ignore")
+    appendLine(" * #[derive(Clone, Serialize, Deserialize)]")
+    appendLine(" * enum Signup { AskName, AskPhoto { name: String } }")
+    appendLine(" *")
+    appendLine(" * let dialogues = Arc::new(Dialogues::new(JsonFileStorage::open(\"signup.json\")?).timeout(Duration::from_secs(600)).cancel_command(\"cancel\"));")
+    appendLine(" * let dispatcher = Dispatcher::new(client, bot, dialogues.clone())")
+    appendLine(" *     .on(dialogues.cancel_filter(), Dialogues::cancel)")
+    appendLine(" *     .on(Filter::command(\"signup\"), signup)")
+    appendLine(" *     .on(dialogues.filter(|state| matches!(state, Signup::AskName)), ask_name);")
+    appendLine(" *")
+    appendLine(" * async fn ask_name(context: Context<Client, Arc<Dialogues<Signup, JsonFileStorage<Signup>>>>) -> HandlerResult {")
+    appendLine(" *     let key = DialogueKey::from_update(&context.update).ok_or(\"no user\")?;")
+    appendLine(" *     let name = context.update.effective_message().and_then(|message| message.text.clone()).ok_or(\"no text\")?;")
+    appendLine(" *     context.state.set(&key, Signup::AskPhoto { name })?;")
+    appendLine(" *     Ok(())")
+    appendLine(" * }")
+    appendLine(" * ```")
+    appendLine(" * */")
+    appendLine("pub struct Dialogues<S, St> {")
+    appendLine("    storage: St,")
+    appendLine("    timeout: Option<std::time::Duration>,")
+    appendLine("    cancel_commands: Vec<String>,")
+    appendLine("    state: std::marker::PhantomData<fn() -> S>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<S: Clone + Send + Sync + 'static, St: Storage<S> + 'static> Dialogues<S, St> {")
+    appendLine("    /// Conversations saved in `storage`, without timeout or cancel command.")
+    appendLine("    pub fn new(storage: St) -> Self {")
+    appendLine("        Self { storage, timeout: None, cancel_commands: Vec::new(), state: std::marker::PhantomData }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Ends the conversations without changes for `timeout`.")
+    appendLine("    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {")
+    appendLine("        self.timeout = Some(timeout);")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds a command matched by [Dialogues::cancel_filter], e.g. `cancel` for `/cancel`.")
+    appendLine("    pub fn cancel_command(mut self, name: impl Into<String>) -> Self {")
+    appendLine("        self.cancel_commands.push(name.into());")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The state of the conversation of `key`, None when there is none or it expired (it's then removed).")
+    appendLine("    pub fn get(&self, key: &DialogueKey) -> Result<Option<S>, St::Error> {")
+    appendLine("        let Some(stored) = self.storage.get(key)? else {")
+    appendLine("            return Ok(None);")
+    appendLine("        };")
+    appendLine("        if self.expired(&stored) {")
+    appendLine("            self.storage.remove(key)?;")
+    appendLine("            return Ok(None);")
+    appendLine("        }")
+    appendLine("        Ok(Some(stored.state))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn expired(&self, stored: &StoredState<S>) -> bool {")
+    appendLine("        self.timeout.is_some_and(|timeout| u128::from(dialogue_now().saturating_sub(stored.updated)) >= timeout.as_millis())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Starts or continues the conversation of `key` with `state`.")
+    appendLine("    pub fn set(&self, key: &DialogueKey, state: S) -> Result<(), St::Error> {")
+    appendLine("        self.storage.set(key, StoredState { state, updated: dialogue_now() })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Ends the conversation of `key`.")
+    appendLine("    pub fn exit(&self, key: &DialogueKey) -> Result<(), St::Error> {")
+    appendLine("        self.storage.remove(key)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The state of the conversation of `update`, see [DialogueKey::from_update].")
+    appendLine("    pub fn get_update(&self, update: &Update) -> Result<Option<S>, St::Error> {")
+    appendLine("        match DialogueKey::from_update(update) {")
+    appendLine("            Some(key) => self.get(&key),")
+    appendLine("            None => Ok(None),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Ends the conversation of `update`, see [DialogueKey::from_update].")
+    appendLine("    pub fn exit_update(&self, update: &Update) -> Result<(), St::Error> {")
+    appendLine("        match DialogueKey::from_update(update) {")
+    appendLine("            Some(key) => self.exit(&key),")
+    appendLine("            None => Ok(()),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /**")
+    appendLine("     * Updates of a conversation with a state passing `test`. A storage failing is a conversation without state.")
+    appendLine("     * The filter only reads the storage, an expired state is removed by the next [Dialogues::get] or [Dialogues::set].")
+    appendLine("     * */")
+    appendLine("    pub fn filter(self: &std::sync::Arc<Self>, test: impl Fn(&S) -> bool + Send + Sync + 'static) -> Filter {")
+    appendLine("        let dialogues = self.clone();")
+    appendLine("        Filter::new(move |update, _| {")
+    appendLine("            let stored = DialogueKey::from_update(update).and_then(|key| dialogues.storage.get(&key).ok().flatten());")
+    appendLine("            stored.is_some_and(|stored| !dialogues.expired(&stored) && test(&stored.state))")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Cancel commands sent in a conversation, its handler exits it, e.g. [Dialogues::cancel].")
+    appendLine("    pub fn cancel_filter(self: &std::sync::Arc<Self>) -> Filter {")
+    appendLine("        let commands = self.cancel_commands.clone();")
+    appendLine("        let cancel = Filter::new(move |update, bot| {")
+    appendLine("            let command = update.effective_message().and_then(|message| message.command(bot));")
+    appendLine("            command.is_some_and(|command| commands.iter().any(|name| command.name.eq_ignore_ascii_case(name)))")
+    appendLine("        });")
+    appendLine("        cancel & self.filter(|_| true)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Handler of [Dialogues::cancel_filter] ending the conversation, for a [Dispatcher] whose state is the dialogues.")
+    appendLine("    pub async fn cancel<C: Send + Sync + 'static>(context: Context<C, std::sync::Arc<Self>>) -> HandlerResult {")
+    appendLine("        context.state.exit_update(&context.update)?;")
+    appendLine("        Ok(())")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("// Unix time in milliseconds of the saved states")
+    appendLine("fn dialogue_now() -> u64 {")
+    appendLine("    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64)")
+    appendLine("}")
+}
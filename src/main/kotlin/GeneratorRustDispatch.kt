@@ -0,0 +1,465 @@
+// Message fields telling its content, venue before location since a venue has both
+private val rustContentFields = listOf(
+    "text", "animation", "audio", "document", "paid_media", "photo", "sticker", "story", "video", "video_note", "voice",
+    "contact", "dice", "game", "poll", "venue", "location", "invoice", "successful_payment", "users_shared", "chat_shared",
+    "web_app_data", "new_chat_members", "left_chat_member", "pinned_message"
+)
+
+/**
+ * Routing of the updates: `UpdateKind`, `ContentKind` and the `effective_*` accessors of `Update` read from the docs,
+ * and the `Dispatcher` running the async handlers selected by composable `Filter`s.
+ *
+ * @param boxed fields and variants boxed in the models, e.g. "MaybeInaccessibleMessage.Message"
+ */
+fun List<DocSection>.toRustDispatch(boxed: Set<String>) = buildString {
+    val allType = this@toRustDispatch.flatMap { section -> section.docTypes }
+    val typeByName = allType.associateBy { it.name }
+    val update = typeByName["Update"]
+    val message = typeByName["Message"]
+    val deprecated = this@toRustDispatch.toRustDeprecations().keys
+    if (update != null) {
+        append(update.toRustUpdateKind(typeByName, deprecated))
+        appendLine()
+    }
+    val maybeInaccessible = TelegramType.Super.MaybeInaccessibleMessage
+    val variants = maybeInaccessible.rustVariants(allType)
+    if (variants.isNotEmpty()) {
+        if (variants.any { it.name in deprecated || "${it.name}.chat" in deprecated }) appendLine(rustAllowDeprecated)
+        appendLine("impl ${maybeInaccessible.name} {")
+        appendLine("    /// The chat of the message, accessible or not.")
+        appendLine("    pub fn chat(&self) -> Option<&Chat> {")
+        appendLine("        match self {")
+        variants.forEach { type ->
+            val hasChat = type.docFields.any { it.name == "chat" && it.required && it.type.name == "Chat" }
+            val variant = maybeInaccessible.toRustVariantName(type)
+            appendLine(if (hasChat) "            Self::$variant(value) => Some(&value.chat)," else "            Self::$variant(_) => None,")
+        }
+        appendLine("            #[cfg(not(feature = \"strict\"))]")
+        appendLine("            Self::Unknown => None,")
+        appendLine("        }")
+        appendLine("    }")
+        variants.firstOrNull { it.name == "Message" }?.let { type ->
+            val value = if ("${maybeInaccessible.name}.${type.name}" in boxed) "value.as_ref()" else "value"
+            appendLine()
+            appendLine("    /// The message, when it's accessible.")
+            appendLine("    pub fn message(&self) -> Option<&Message> {")
+            appendLine("        match self {")
+            appendLine("            Self::${maybeInaccessible.toRustVariantName(type)}(value) => Some($value),")
+            appendLine("            _ => None,")
+            appendLine("        }")
+            appendLine("    }")
+        }
+        appendLine("}")
+        appendLine()
+    }
+    if (message != null) {
+        append(message.toRustContentKind(deprecated))
+        appendLine()
+    }
+    // Example: std::time::Duration::from_secs(30), the timeout has the unit type of the request field
+    val pollTimeout = this@toRustDispatch.flatMap { section -> section.docMethods }
+        .firstOrNull { it.name == "getUpdates" }?.docParameters?.firstOrNull { it.name == "timeout" }
+        ?.type?.toRustUnitValue(rustPollTimeoutSeconds.toString())
+    append(rustDispatch(pollTimeout))
+}
+
+// Long polling timeout of Dispatcher::poll when the request has none
+internal const val rustPollTimeoutSeconds = 30
+
+// UpdateKind, Update::kind and the effective_* accessors
+private fun DocType.toRustUpdateKind(typeByName: Map<String, DocType>, deprecated: Set<String>) = buildString {
+    val kinds = docFields.filter { !it.required }
+    // fields read by the accessors, e.g. "CallbackQuery.from"
+    val members = kinds.flatMap { field ->
+        typeByName[field.type.name]?.docFields.orEmpty().filter { it.name in listOf("chat", "message", "from", "user") }
+            .map { "${field.type.name}.${it.name}" }
+    }
+    appendLine("/// Kind of an [Update], one per optional field, e.g. [UpdateKind::CallbackQuery] for `callback_query`.")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub enum UpdateKind {")
+    kinds.forEach { appendLine("    ${it.name.toRustCamelCase()},") }
+    appendLine("}")
+    appendLine()
+    appendLine("impl UpdateKind {")
+    appendLine("    /// Every kind, in the order of the fields of [Update].")
+    appendLine("    pub const ALL: [UpdateKind; ${kinds.size}] = [")
+    kinds.forEach { appendLine("        Self::${it.name.toRustCamelCase()},") }
+    appendLine("    ];")
+    appendLine()
+    appendLine("    /// Name of the field, as listed in `allowed_updates`, e.g. `callback_query`.")
+    appendLine("    pub fn name(self) -> &'static str {")
+    appendLine("        match self {")
+    kinds.forEach { appendLine("            Self::${it.name.toRustCamelCase()} => \"${it.name}\",") }
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    if ((rustUses() + members).any { it in deprecated }) appendLine(rustAllowDeprecated)
+    appendLine("impl Update {")
+    appendLine("    /// Kind of the update, None for a kind added after these models.")
+    appendLine("    pub fn kind(&self) -> Option<UpdateKind> {")
+    kinds.forEach { field ->
+        appendLine("        if self.${field.name}.is_some() {")
+        appendLine("            return Some(UpdateKind::${field.name.toRustCamelCase()});")
+        appendLine("        }")
+    }
+    appendLine("        None")
+    appendLine("    }")
+    // Example: self.message.as_ref().map(|value| &value.chat)
+    fun DocField.access(member: DocField) = if (member.required) {
+        "self.$name.as_ref().map(|value| &value.${member.name})"
+    } else {
+        "self.$name.as_ref().and_then(|value| value.${member.name}.as_ref())"
+    }
+
+    fun accessor(doc: String, signature: String, accesses: List<Pair<DocField, String>>) {
+        if (accesses.isEmpty()) return
+        val names = accesses.map { "`${it.first.name}`" }
+        val fields = if (names.size == 1) names.single() else "${names.dropLast(1).joinToString()} or ${names.last()}"
+        appendLine()
+        appendLine("    /// $doc $fields.")
+        appendLine("    pub fn $signature {")
+        accesses.forEachIndexed { index, (_, access) ->
+            appendLine(if (index == 0) "        $access" else "            .or_else(|| $access)")
+        }
+        appendLine("    }")
+    }
+    accessor(
+        "The message of", "effective_message(&self) -> Option<&Message>",
+        kinds.filter { it.type.name == "Message" }.map { it to "self.${it.name}.as_ref()" }
+    )
+    accessor("The chat of", "effective_chat(&self) -> Option<&Chat>", kinds.mapNotNull { field ->
+        val fields = typeByName[field.type.name]?.docFields.orEmpty()
+        val chat = fields.firstOrNull { it.name == "chat" && it.type.name == "Chat" }
+        val message = fields.firstOrNull { it.name == "message" && it.type == TelegramType.Super.MaybeInaccessibleMessage }
+        when {
+            chat != null -> field to field.access(chat)
+            message != null -> field to "${field.access(message)}.and_then(MaybeInaccessibleMessage::chat)"
+            else -> null
+        }
+    })
+    accessor("The user of", "effective_user(&self) -> Option<&User>", kinds.mapNotNull { field ->
+        val fields = typeByName[field.type.name]?.docFields.orEmpty()
+        listOf("from", "user").firstNotNullOfOrNull { name -> fields.firstOrNull { it.name == name && it.type.name == "User" } }
+            ?.let { field to field.access(it) }
+    })
+    appendLine("}")
+}
+
+// ContentKind and Message::has_content
+private fun DocType.toRustContentKind(deprecated: Set<String>) = buildString {
+    val contents = rustContentFields.mapNotNull { name -> docFields.firstOrNull { it.name == name && !it.required } }
+    appendLine("/// Content of a [Message], see [Message::has_content].")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub enum ContentKind {")
+    contents.forEach { appendLine("    ${it.name.toRustCamelCase()},") }
+    appendLine("}")
+    appendLine()
+    if (name in deprecated || contents.any { "$name.${it.name}" in deprecated }) appendLine(rustAllowDeprecated)
+    appendLine("impl $name {")
+    appendLine("    /// Whether the message has `kind`, e.g. a `photo` for [ContentKind::Photo].")
+    appendLine("    pub fn has_content(&self, kind: ContentKind) -> bool {")
+    appendLine("        match kind {")
+    contents.forEach { appendLine("            ContentKind::${it.name.toRustCamelCase()} => self.${it.name}.is_some(),") }
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+}
+
+private fun rustDispatch(pollTimeout: String?) = buildString {
+    appendLine("/// Error returned by a handler.")
+    appendLine("pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;")
+    appendLine()
+    appendLine("/// Result of a handler, see [Dispatcher::on].")
+    appendLine("pub type HandlerResult = Result<(), HandlerError>;")
+    appendLine()
+    appendLine("type BoxFuture<T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send>>;")
+    appendLine()
+    appendLine("type FilterFn = dyn Fn(&Update, &User) -> bool + Send + Sync;")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * A condition on an [Update], combined with `&`, `|` and `!`:")
+    appendLine(" * `Filter::command(\"start\") | Filter::callback_prefix(\"start:\")`.")
+    appendLine(" * */")
+    appendLine("#[derive(Clone)]")
+    appendLine("pub struct Filter {")
+    appendLine("    test: std::sync::Arc<FilterFn>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::fmt::Debug for Filter {")
+    appendLine("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")
+    appendLine("        f.write_str(\"Filter\")")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Filter {")
+    appendLine("    /// A filter testing the update and the bot user.")
+    appendLine("    pub fn new(test: impl Fn(&Update, &User) -> bool + Send + Sync + 'static) -> Self {")
+    appendLine("        Self { test: std::sync::Arc::new(test) }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Whether `update` passes the filter, `bot` is the user returned by `getMe`.")
+    appendLine("    pub fn matches(&self, update: &Update, bot: &User) -> bool {")
+    appendLine("        (self.test)(update, bot)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Every update.")
+    appendLine("    pub fn any() -> Self {")
+    appendLine("        Self::new(|_, _| true)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Updates of one of `kinds`.")
+    appendLine("    pub fn kind(kinds: impl IntoIterator<Item = UpdateKind>) -> Self {")
+    appendLine("        let kinds: Vec<UpdateKind> = kinds.into_iter().collect();")
+    appendLine("        Self::new(move |update, _| update.kind().is_some_and(|kind| kinds.contains(&kind)))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Updates with a chat of one of `types`, e.g. \"private\", \"group\", \"supergroup\" or \"channel\".")
+    appendLine("    pub fn chat_type(types: impl IntoIterator<Item = impl Into<String>>) -> Self {")
+    appendLine("        let types: Vec<String> = types.into_iter().map(Into::into).collect();")
+    appendLine("        Self::new(move |update, _| update.effective_chat().is_some_and(|chat| types.contains(&chat.type_)))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Updates of one of the users `ids`.")
+    appendLine("    pub fn user(ids: impl IntoIterator<Item = Integer>) -> Self {")
+    appendLine("        let ids: Vec<Integer> = ids.into_iter().collect();")
+    appendLine("        Self::new(move |update, _| update.effective_user().is_some_and(|user| ids.contains(&user.id)))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Messages with the command `name` for this bot, see [Message::command].")
+    appendLine("    pub fn command(name: impl Into<String>) -> Self {")
+    appendLine("        let name = name.into();")
+    appendLine("        Self::new(move |update, bot| {")
+    appendLine("            update.effective_message().and_then(|message| message.command(bot)).is_some_and(|command| command.name.eq_ignore_ascii_case(&name))")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Messages with one of the commands of `C`, see [BotCommands::parse].")
+    appendLine("    pub fn commands<C: BotCommands>() -> Self {")
+    appendLine("        Self::new(|update, bot| update.effective_message().and_then(|message| C::from_message(message, bot)).is_some_and(|command| command.is_ok()))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Callback queries with `data` starting with `prefix`, e.g. a `CallbackData::PREFIX`.")
+    appendLine("    pub fn callback_prefix(prefix: impl Into<String>) -> Self {")
+    appendLine("        let prefix = prefix.into();")
+    appendLine("        Self::new(move |update, _| {")
+    appendLine("            update.callback_query.as_ref().and_then(|query| query.data.as_deref()).is_some_and(|data| data.starts_with(&prefix))")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Messages with a `text` or `caption` passing `test`.")
+    appendLine("    pub fn text(test: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {")
+    appendLine("        Self::new(move |update, _| {")
+    appendLine("            update.effective_message().and_then(|message| message.text.as_deref().or(message.caption.as_deref())).is_some_and(&test)")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Messages with a `text` or `caption` matching `regex`.")
+    appendLine("    #[cfg(feature = \"regex\")]")
+    appendLine("    pub fn regex(regex: regex::Regex) -> Self {")
+    appendLine("        Self::text(move |text| regex.is_match(text))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Messages with one of the content `kinds`, e.g. [ContentKind::Photo].")
+    appendLine("    pub fn content(kinds: impl IntoIterator<Item = ContentKind>) -> Self {")
+    appendLine("        let kinds: Vec<ContentKind> = kinds.into_iter().collect();")
+    appendLine("        Self::new(move |update, _| update.effective_message().is_some_and(|message| kinds.iter().any(|kind| message.has_content(*kind))))")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::ops::BitAnd for Filter {")
+    appendLine("    type Output = Filter;")
+    appendLine()
+    appendLine("    fn bitand(self, other: Filter) -> Filter {")
+    appendLine("        Filter::new(move |update, bot| self.matches(update, bot) && other.matches(update, bot))")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::ops::BitOr for Filter {")
+    appendLine("    type Output = Filter;")
+    appendLine()
+    appendLine("    fn bitor(self, other: Filter) -> Filter {")
+    appendLine("        Filter::new(move |update, bot| self.matches(update, bot) || other.matches(update, bot))")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::ops::Not for Filter {")
+    appendLine("    type Output = Filter;")
+    appendLine()
+    appendLine("    fn not(self) -> Filter {")
+    appendLine("        Filter::new(move |update, bot| !self.matches(update, bot))")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// What a handler receives: the update with the client, the bot user and the state of the [Dispatcher].")
+    appendLine("pub struct Context<C, S> {")
+    appendLine("    pub update: std::sync::Arc<Update>,")
+    appendLine("    pub client: std::sync::Arc<C>,")
+    appendLine("    /// The user returned by `getMe`.")
+    appendLine("    pub bot: std::sync::Arc<User>,")
+    appendLine("    pub state: std::sync::Arc<S>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<C, S> Clone for Context<C, S> {")
+    appendLine("    fn clone(&self) -> Self {")
+    appendLine("        Self {")
+    appendLine("            update: self.update.clone(),")
+    appendLine("            client: self.client.clone(),")
+    appendLine("            bot: self.bot.clone(),")
+    appendLine("            state: self.state.clone(),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A handler failing on an update, see [Dispatcher::on_error].")
+    appendLine("#[derive(Debug)]")
+    appendLine("pub struct DispatchError {")
+    appendLine("    /// Name of the handler, the path of its function for a function.")
+    appendLine("    pub handler: String,")
+    appendLine("    pub update_id: Integer,")
+    appendLine("    pub error: HandlerError,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::fmt::Display for DispatchError {")
+    appendLine("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")
+    appendLine("        write!(f, \"handler {} failed on update {}: {}\", self.handler, self.update_id, self.error)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl std::error::Error for DispatchError {")
+    appendLine("    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {")
+    appendLine("        Some(self.error.as_ref())")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("struct Route<C, S> {")
+    appendLine("    name: String,")
+    appendLine("    filter: Filter,")
+    appendLine("    fallthrough: bool,")
+    appendLine("    handler: Box<dyn Fn(Context<C, S>) -> BoxFuture<HandlerResult> + Send + Sync>,")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Routes each [Update] to the handlers whose filter matches, in the order they were added.")
+    appendLine(" * A handler added with [Dispatcher::on] ends the dispatch, one added with [Dispatcher::on_fallthrough]")
+    appendLine(" * lets the next matching handlers run. Failed handlers are reported to [Dispatcher::on_error].")
+    appendLine(" *")
+    appendLine(" * ```ignore")
+    appendLine(" * let dispatcher = Dispatcher::new(client, bot, State::default())")
+    appendLine(" *     .on(Filter::command(\"start\"), start)")
+    appendLine(" *     .on(Filter::callback_prefix(Vote::PREFIX), vote)")
+    appendLine(" *     .on(Filter::content([ContentKind::Photo]) & Filter::chat_type([\"private\"]), photo);")
+    appendLine(" *")
+    appendLine(" * async fn start(context: Context<Client, State>) -> HandlerResult { ... }")
+    appendLine(" * ```")
+    appendLine(" * */")
+    appendLine("pub struct Dispatcher<C, S> {")
+    appendLine("    client: std::sync::Arc<C>,")
+    appendLine("    bot: std::sync::Arc<User>,")
+    appendLine("    state: std::sync::Arc<S>,")
+    appendLine("    routes: Vec<Route<C, S>>,")
+    appendLine("    on_error: Box<dyn Fn(&DispatchError) + Send + Sync>,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<C: Send + Sync + 'static, S: Send + Sync + 'static> Dispatcher<C, S> {")
+    appendLine("    /// A dispatcher without handlers, `bot` is the user returned by `getMe`. Errors are ignored until [Dispatcher::on_error].")
+    appendLine("    pub fn new(client: C, bot: User, state: S) -> Self {")
+    appendLine("        Self {")
+    appendLine("            client: std::sync::Arc::new(client),")
+    appendLine("            bot: std::sync::Arc::new(bot),")
+    appendLine("            state: std::sync::Arc::new(state),")
+    appendLine("            routes: Vec::new(),")
+    appendLine("            on_error: Box::new(|_| {}),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds `handler` for the updates matching `filter`, the dispatch ends after it.")
+    appendLine("    pub fn on<F, Fut>(self, filter: Filter, handler: F) -> Self")
+    appendLine("    where")
+    appendLine("        F: Fn(Context<C, S>) -> Fut + Send + Sync + 'static,")
+    appendLine("        Fut: std::future::Future<Output = HandlerResult> + Send + 'static,")
+    appendLine("    {")
+    appendLine("        self.route(filter, handler, false)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds `handler` for the updates matching `filter`, the next matching handlers run after it, e.g. to log updates.")
+    appendLine("    pub fn on_fallthrough<F, Fut>(self, filter: Filter, handler: F) -> Self")
+    appendLine("    where")
+    appendLine("        F: Fn(Context<C, S>) -> Fut + Send + Sync + 'static,")
+    appendLine("        Fut: std::future::Future<Output = HandlerResult> + Send + 'static,")
+    appendLine("    {")
+    appendLine("        self.route(filter, handler, true)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Reports the handlers failing, e.g. to a logger.")
+    appendLine("    pub fn on_error(mut self, on_error: impl Fn(&DispatchError) + Send + Sync + 'static) -> Self {")
+    appendLine("        self.on_error = Box::new(on_error);")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn route<F, Fut>(mut self, filter: Filter, handler: F, fallthrough: bool) -> Self")
+    appendLine("    where")
+    appendLine("        F: Fn(Context<C, S>) -> Fut + Send + Sync + 'static,")
+    appendLine("        Fut: std::future::Future<Output = HandlerResult> + Send + 'static,")
+    appendLine("    {")
+    appendLine("        self.routes.push(Route {")
+    appendLine("            name: std::any::type_name::<F>().to_string(),")
+    appendLine("            filter,")
+    appendLine("            fallthrough,")
+    appendLine("            handler: Box::new(move |context| Box::pin(handler(context))),")
+    appendLine("        });")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The context given to the handlers of `update`.")
+    appendLine("    pub fn context(&self, update: Update) -> Context<C, S> {")
+    appendLine("        Context {")
+    appendLine("            update: std::sync::Arc::new(update),")
+    appendLine("            client: self.client.clone(),")
+    appendLine("            bot: self.bot.clone(),")
+    appendLine("            state: self.state.clone(),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Runs the handlers of `update`, returning how many ran. A failed handler ends the dispatch like a successful one.")
+    appendLine("    pub async fn dispatch(&self, update: Update) -> usize {")
+    appendLine("        let context = self.context(update);")
+    appendLine("        let mut count = 0;")
+    appendLine("        for route in &self.routes {")
+    appendLine("            if !route.filter.matches(&context.update, &context.bot) {")
+    appendLine("                continue;")
+    appendLine("            }")
+    appendLine("            count += 1;")
+    appendLine("            if let Err(error) = (route.handler)(context.clone()).await {")
+    appendLine("                (self.on_error)(&DispatchError { handler: route.name.clone(), update_id: context.update.update_id, error });")
+    appendLine("            }")
+    appendLine("            if !route.fallthrough {")
+    appendLine("                break;")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("        count")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<C: TelegramClient + Send + Sync + 'static, S: Send + Sync + 'static> Dispatcher<C, S> {")
+    appendLine("    /**")
+    appendLine("     * Dispatches the updates received with `getUpdates` one by one, returning the first error of the client,")
+    if (pollTimeout != null) {
+        appendLine("     * e.g. to retry after a delay. Without a `timeout`, the request long polls for $rustPollTimeoutSeconds seconds")
+        appendLine("     * instead of returning at once when there is no update.")
+    } else {
+        appendLine("     * e.g. to retry after a delay.")
+    }
+    appendLine("     * */")
+    appendLine("    pub async fn poll(&self, request: GetUpdatesRequest) -> Result<(), C::Error> {")
+    appendLine("        let mut request = request;")
+    if (pollTimeout != null) appendLine("        request.timeout = request.timeout.or(Some($pollTimeout));")
+    appendLine("        loop {")
+    appendLine("            for update in self.client.call(&request).await? {")
+    appendLine("                request.offset = Some(update.update_id + 1);")
+    appendLine("                self.dispatch(update).await;")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+}
@@ -0,0 +1,175 @@
+/**
+ * Builders of the keyboards: `InlineKeyboard` and `ReplyKeyboard` add rows, grids and pages of buttons,
+ * the buttons are created with the constructors of each action, e.g. `InlineKeyboardButton::new_callback_data`.
+ */
+fun List<DocSection>.toRustKeyboards() = buildString {
+    append(rustKeyboards())
+}
+
+private fun rustKeyboards() = buildString {
+    appendLine("/**")
+    appendLine(" * Rows of buttons, built one row or grid at a time: [InlineKeyboard] builds an [InlineKeyboardMarkup],")
+    appendLine(" * [ReplyKeyboard] a [ReplyKeyboardMarkup].")
+    appendLine(" * */")
+    appendLine("#[derive(Clone, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct Keyboard<B> {")
+    appendLine("    rows: Vec<Vec<B>>,")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Builder of an [InlineKeyboardMarkup], e.g. `InlineKeyboard::new().row([InlineKeyboardButton::new_url(\"Docs\", url)]).build()`.")
+    appendLine("pub type InlineKeyboard = Keyboard<InlineKeyboardButton>;")
+    appendLine()
+    appendLine("/// Builder of a [ReplyKeyboardMarkup], e.g. `ReplyKeyboard::new().row([\"Yes\", \"No\"]).build().resize_keyboard(true)`.")
+    appendLine("pub type ReplyKeyboard = Keyboard<KeyboardButton>;")
+    appendLine()
+    appendLine("impl<B> Default for Keyboard<B> {")
+    appendLine("    fn default() -> Self {")
+    appendLine("        Self { rows: Vec::new() }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl<B> Keyboard<B> {")
+    appendLine("    pub fn new() -> Self {")
+    appendLine("        Self::default()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds a row with `buttons`, nothing when it's empty.")
+    appendLine("    pub fn row(mut self, buttons: impl IntoIterator<Item = impl Into<B>>) -> Self {")
+    appendLine("        let row: Vec<B> = buttons.into_iter().map(Into::into).collect();")
+    appendLine("        if !row.is_empty() {")
+    appendLine("            self.rows.push(row);")
+    appendLine("        }")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds `button` at the end of the last row.")
+    appendLine("    pub fn button(mut self, button: impl Into<B>) -> Self {")
+    appendLine("        match self.rows.last_mut() {")
+    appendLine("            Some(row) => row.push(button.into()),")
+    appendLine("            None => self.rows.push(vec![button.into()]),")
+    appendLine("        }")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Adds `buttons` in rows of `columns` buttons, the last row may be shorter.")
+    appendLine("    pub fn grid(mut self, buttons: impl IntoIterator<Item = impl Into<B>>, columns: usize) -> Self {")
+    appendLine("        let mut row = Vec::new();")
+    appendLine("        for button in buttons {")
+    appendLine("            row.push(button.into());")
+    appendLine("            if row.len() >= columns {")
+    appendLine("                self.rows.push(std::mem::take(&mut row));")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("        self.row(row)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The rows added so far.")
+    appendLine("    pub fn rows(&self) -> &[Vec<B>] {")
+    appendLine("        &self.rows")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// The rows, e.g. to append them to another keyboard.")
+    appendLine("    pub fn into_rows(self) -> Vec<Vec<B>> {")
+    appendLine("        self.rows")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl InlineKeyboard {")
+    appendLine("    /**")
+    appendLine("     * Adds the items of `page` as a grid with a `button` each, followed by a navigation row when there are more")
+    appendLine("     * pages: `‹` and `›` buttons around the page number, sending the `callback_data` of the page they show.")
+    appendLine("     * */")
+    appendLine("    pub fn page<T>(")
+    appendLine("        self,")
+    appendLine("        items: &[T],")
+    appendLine("        page: Page,")
+    appendLine("        button: impl FnMut(&T) -> InlineKeyboardButton,")
+    appendLine("        callback_data: impl Fn(usize) -> String,")
+    appendLine("    ) -> Self {")
+    appendLine("        let pages = page.count(items.len());")
+    appendLine("        let index = page.index.min(pages - 1);")
+    appendLine("        let keyboard = self.grid(items[page.range(items.len())].iter().map(button), page.columns);")
+    appendLine("        if pages == 1 {")
+    appendLine("            return keyboard;")
+    appendLine("        }")
+    appendLine("        let previous = (index > 0).then(|| InlineKeyboardButton::new_callback_data(\"‹\", callback_data(index - 1)));")
+    appendLine("        let current = InlineKeyboardButton::new_callback_data(format!(\"{}/{}\", index + 1, pages), callback_data(index));")
+    appendLine("        let next = (index + 1 < pages).then(|| InlineKeyboardButton::new_callback_data(\"›\", callback_data(index + 1)));")
+    appendLine("        keyboard.row(previous.into_iter().chain([current]).chain(next))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    pub fn build(self) -> InlineKeyboardMarkup {")
+    appendLine("        InlineKeyboardMarkup::new(self.rows)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<InlineKeyboard> for InlineKeyboardMarkup {")
+    appendLine("    fn from(keyboard: InlineKeyboard) -> Self {")
+    appendLine("        keyboard.build()")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl ReplyKeyboard {")
+    appendLine("    /// The markup, its options are set with its setters, e.g. `resize_keyboard(true)`.")
+    appendLine("    pub fn build(self) -> ReplyKeyboardMarkup {")
+    appendLine("        ReplyKeyboardMarkup::new(self.rows)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<ReplyKeyboard> for ReplyKeyboardMarkup {")
+    appendLine("    fn from(keyboard: ReplyKeyboard) -> Self {")
+    appendLine("        keyboard.build()")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A simple text button, sent as a message when pressed.")
+    appendLine("impl From<&str> for KeyboardButton {")
+    appendLine("    fn from(text: &str) -> Self {")
+    appendLine("        KeyboardButton::new(text)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<String> for KeyboardButton {")
+    appendLine("    fn from(text: String) -> Self {")
+    appendLine("        KeyboardButton::new(text)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// A page of a list shown with [InlineKeyboard::page].")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub struct Page {")
+    appendLine("    /// Index of the page, from 0, the last page when it's after it.")
+    appendLine("    pub index: usize,")
+    appendLine("    /// Items per page, 0 for every item in one page.")
+    appendLine("    pub size: usize,")
+    appendLine("    /// Buttons per row.")
+    appendLine("    pub columns: usize,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Page {")
+    appendLine("    /// The page `index` of `size` items, one per row.")
+    appendLine("    pub fn new(index: usize, size: usize) -> Self {")
+    appendLine("        Self { index, size, columns: 1 }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Sets the buttons per row.")
+    appendLine("    pub fn columns(mut self, columns: usize) -> Self {")
+    appendLine("        self.columns = columns.max(1);")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Number of pages of `len` items, at least 1.")
+    appendLine("    pub fn count(&self, len: usize) -> usize {")
+    appendLine("        if self.size == 0 { 1 } else { len.div_ceil(self.size).max(1) }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Indexes of the items of this page among `len` items.")
+    appendLine("    pub fn range(&self, len: usize) -> std::ops::Range<usize> {")
+    appendLine("        if self.size == 0 {")
+    appendLine("            return 0..len;")
+    appendLine("        }")
+    appendLine("        let start = self.index.min(self.count(len) - 1) * self.size;")
+    appendLine("        start.min(len)..(start + self.size).min(len)")
+    appendLine("    }")
+    appendLine("}")
+}
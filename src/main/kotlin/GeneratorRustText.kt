@@ -0,0 +1,804 @@
+/**
+ * Helpers for the text of the messages: UTF-16 offsets of the entities, typed access to their fields,
+ * [FormattedText] to build formatted text, rendering of the received text back to HTML or MarkdownV2 and splitting
+ * of the texts longer than the limits.
+ *
+ * @param inputTypes types sent in requests, they get setters taking a FormattedText
+ */
+fun List<DocSection>.toRustText(inputTypes: Set<String>) = buildString {
+    val allType = this@toRustText.flatMap { section -> section.docTypes }
+    val allRequest = this@toRustText.flatMap { section -> section.docMethods }
+        .filter { it.docParameters.isNotEmpty() }
+        .map { method -> method.name to method.docParameters.map { DocField(it.name, it.description, it.type, it.required) } }
+    val deprecated = this@toRustText.toRustDeprecations().keys
+    // Example: "sendMessage" -> SendMessageRequest, types keep their name
+    fun structName(owner: String) = if (owner.first().isLowerCase()) "${owner.capitalize()}Request" else owner
+    fun allowDeprecated(owner: String, fields: List<DocField>) = owner in deprecated || fields.any { "$owner.${it.name}" in deprecated }
+    append(rustEntities())
+    appendLine()
+    append(rustFormattedText())
+    appendLine()
+    append(rustSplitText())
+    allType.forEach { type ->
+        val entities = type.docFields.mapNotNull { field -> type.docFields.rustTextOf(field)?.let { field to it } }
+            .distinctBy { (_, text) -> text.name }
+        if (entities.isNotEmpty()) {
+            appendLine()
+            if (allowDeprecated(type.name, entities.flatMap { it.toList() })) appendLine(rustAllowDeprecated)
+            appendLine("impl ${type.name} {")
+            entities.forEachIndexed { index, (field, text) ->
+                if (index > 0) appendLine()
+                val textValue = if (text.required) "Some(self.${text.name}.as_str())" else "self.${text.name}.as_deref()"
+                val entitiesValue = if (field.required) "Some(self.${field.name}.as_slice())" else "self.${field.name}.as_deref()"
+                appendLine("    /// `${field.name}` with the part of `${text.name}` they cover, see [MessageEntity::text_in].")
+                appendLine("    pub fn parse_${field.name}(&self) -> impl Iterator<Item = (&MessageEntity, &str)> {")
+                appendLine("        entities_with_text($textValue, $entitiesValue)")
+                appendLine("    }")
+                appendLine()
+                val entitiesSlice = if (field.required) "&self.${field.name}" else "self.${field.name}.as_deref().unwrap_or_default()"
+                appendLine("    /// `${text.name}` with `${field.name}` rendered in `markup`.")
+                if (text.required) {
+                    appendLine("    pub fn render_${text.name}(&self, markup: Markup) -> String {")
+                    appendLine("        markup.render(&self.${text.name}, $entitiesSlice)")
+                } else {
+                    appendLine("    pub fn render_${text.name}(&self, markup: Markup) -> Option<String> {")
+                    appendLine("        self.${text.name}.as_deref().map(|text| markup.render(text, $entitiesSlice))")
+                }
+                appendLine("    }")
+            }
+            appendLine("}")
+        }
+    }
+    val inputs = allType.filter { it.name in inputTypes }.map { it.name to it.docFields } + allRequest
+    inputs.forEach { (owner, fields) ->
+        fields.toRustFormattedSetters(structName(owner), allowDeprecated(owner, fields))?.let {
+            appendLine()
+            append(it)
+        }
+    }
+    allRequest.filter { (owner, _) -> owner.startsWith("send") }.forEach { (owner, fields) ->
+        fields.toRustSplitText(structName(owner), allowDeprecated(owner, fields))?.let {
+            appendLine()
+            append(it)
+        }
+    }
+}
+
+// Example: caption_entities -> caption, entities -> text, text_entities -> text, entities -> message_text
+private fun List<DocField>.rustTextOf(field: DocField): DocField? {
+    if (field.type.name != "List<MessageEntity>") return null
+    val names = listOf(field.name.removeSuffix("entities").removeSuffix("_"), "text", "message_text")
+    return names.firstNotNullOfOrNull { name -> firstOrNull { it.name == name && it.type == TelegramType.StringType } }
+}
+
+// Setters of the text and its entities from a FormattedText, e.g. SendMessageRequest::formatted_text
+private fun List<DocField>.toRustFormattedSetters(structName: String, allowDeprecated: Boolean): String? {
+    val fields = this
+    val texts = mapNotNull { field -> rustTextOf(field)?.let { field to it } }
+    if (texts.isEmpty()) return null
+    return buildString {
+        if (allowDeprecated) appendLine(rustAllowDeprecated)
+        appendLine("impl $structName {")
+        texts.forEachIndexed { index, (entities, text) ->
+            val parseMode = listOf("${text.name}_parse_mode", "parse_mode").firstNotNullOfOrNull { name -> fields.firstOrNull { it.name == name } }
+            fun optional(field: DocField, value: String) = if (field.required) value else "Some($value)"
+            if (index > 0) appendLine()
+            appendLine("    /// Sets `${text.name}` and `${entities.name}` from `value`${parseMode?.let { ", without `${it.name}`" }.orEmpty()}.")
+            appendLine("    pub fn formatted_${text.name}(mut self, value: impl Into<FormattedText>) -> Self {")
+            appendLine("        let (text, entities) = value.into().into_parts();")
+            appendLine("        self.${text.name} = ${optional(text, "text")};")
+            appendLine("        self.${entities.name} = (!entities.is_empty()).then_some(entities);")
+            if (parseMode != null) appendLine("        self.${parseMode.name} = None;")
+            appendLine("        self")
+            appendLine("    }")
+            if (parseMode != null) {
+                // ParseMode is provided by the crate and may not deserialize from the markup name
+                val fallible = parseMode.type == TelegramType.ParseMode
+                val parseModeValue = if (fallible) "markup.parse_mode()?" else "markup.name().to_string()"
+                appendLine()
+                if (fallible) {
+                    appendLine("    /// Sets `${text.name}` to `value` escaped for `markup`, with the matching `${parseMode.name}` and without `${entities.name}`, see [Markup::parse_mode].")
+                    appendLine("    pub fn formatted_${text.name}_as(mut self, value: &FormattedText, markup: Markup) -> Result<Self, serde_json::Error> {")
+                } else {
+                    appendLine("    /// Sets `${text.name}` to `value` escaped for `markup`, with the matching `${parseMode.name}` and without `${entities.name}`.")
+                    appendLine("    pub fn formatted_${text.name}_as(mut self, value: &FormattedText, markup: Markup) -> Self {")
+                }
+                appendLine("        self.${text.name} = ${optional(text, "value.to_markup(markup)")};")
+                appendLine("        self.${entities.name} = None;")
+                appendLine("        self.${parseMode.name} = ${optional(parseMode, parseModeValue)};")
+                appendLine(if (fallible) "        Ok(self)" else "        self")
+                appendLine("    }")
+            }
+        }
+        append("}")
+    }
+}
+
+// Example: SendMessageRequest::split_text, SendPhotoRequest::split_caption, for the texts with entities and a maximum length
+private fun List<DocField>.toRustSplitText(structName: String, allowDeprecated: Boolean): String? {
+    val fields = this
+    val texts = mapNotNull { field -> rustTextOf(field)?.let { field to it } }
+        .mapNotNull { (entities, text) -> text.rustLimit()?.takeIf { it.unit == "characters" }?.let { Triple(entities, text, it) } }
+    if (texts.isEmpty()) return null
+    val hasReplyParameters = fields.any { it.name == "reply_parameters" }
+    val hasReplyMarkup = fields.any { it.name == "reply_markup" }
+    return buildString {
+        if (allowDeprecated) appendLine(rustAllowDeprecated)
+        appendLine("impl $structName {")
+        texts.forEachIndexed { index, (entities, text, limit) ->
+            val parseMode = listOf("${text.name}_parse_mode", "parse_mode").firstOrNull { name -> fields.any { it.name == name } }
+            val constant = "${text.name.uppercase()}_MAX_LENGTH"
+            if (index > 0) appendLine()
+            appendLine("    /**")
+            if (text.required) {
+                appendLine("     * Splits `${text.name}` in requests within [$structName::$constant] (${limit.max} ${limit.unit}), see [FormattedText::split].")
+                if (hasReplyParameters) appendLine("     * Only the first request keeps `reply_parameters`.")
+                if (hasReplyMarkup) appendLine("     * Only the last request keeps `reply_markup`.")
+                if (parseMode != null) appendLine("     * A `${text.name}` with `$parseMode` can't be measured, the request is returned as is.")
+                appendLine("     * */")
+                appendLine("    pub fn split_${text.name}(self) -> Vec<Self> {")
+                if (parseMode != null) appendLine("        if self.$parseMode.is_some() { return vec![self]; }")
+                appendLine("        let parts = FormattedText::from_parts(self.${text.name}.as_str(), self.${entities.name}.clone().unwrap_or_default()).split(Self::$constant);")
+                if (hasReplyParameters || hasReplyMarkup) {
+                    if (hasReplyMarkup) appendLine("        let last = parts.len().saturating_sub(1);")
+                    appendLine("        parts.into_iter().enumerate().map(|(index, part)| {")
+                    appendLine("            let mut request = self.clone().formatted_${text.name}(part);")
+                    if (hasReplyParameters) appendLine("            if index > 0 { request.reply_parameters = None; }")
+                    if (hasReplyMarkup) appendLine("            if index < last { request.reply_markup = None; }")
+                    appendLine("            request")
+                    appendLine("        }).collect()")
+                } else {
+                    appendLine("        parts.into_iter().map(|part| self.clone().formatted_${text.name}(part)).collect()")
+                }
+            } else {
+                // The request is sent once, e.g. with its photo, the rest of a caption goes in other messages
+                appendLine("     * Cuts `${text.name}` to its first part within [$structName::$constant] (${limit.max} ${limit.unit}), see [FormattedText::split].")
+                appendLine("     * The other parts are returned, to send e.g. with [SendMessageRequest::formatted_text].")
+                if (parseMode != null) appendLine("     * A `${text.name}` with `$parseMode` can't be measured, the request is returned as is.")
+                appendLine("     * */")
+                appendLine("    pub fn split_${text.name}(self) -> (Self, Vec<FormattedText>) {")
+                if (parseMode != null) appendLine("        if self.$parseMode.is_some() { return (self, Vec::new()); }")
+                appendLine("        let Some(${text.name}) = self.${text.name}.as_deref() else { return (self, Vec::new()) };")
+                appendLine("        let mut parts = FormattedText::from_parts(${text.name}, self.${entities.name}.clone().unwrap_or_default()).split(Self::$constant).into_iter();")
+                appendLine("        let first = parts.next().unwrap_or_default();")
+                appendLine("        (self.formatted_${text.name}(first), parts.collect())")
+            }
+            appendLine("    }")
+        }
+        append("}")
+    }
+}
+
+private fun rustEntities() = buildString {
+    appendLine("/// Conversions between the UTF-16 offsets of [MessageEntity](super::MessageEntity) and the byte indexes of a `str`.")
+    appendLine("pub mod utf16 {")
+    appendLine("    /// Length of `text` in UTF-16 code units.")
+    appendLine("    pub fn len(text: &str) -> usize {")
+    appendLine("        text.chars().map(char::len_utf16).sum()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Byte index of the UTF-16 `offset` in `text`, `None` past the end or inside a surrogate pair.")
+    appendLine("    pub fn byte_index(text: &str, offset: usize) -> Option<usize> {")
+    appendLine("        let mut units = 0;")
+    appendLine("        for (index, c) in text.char_indices() {")
+    appendLine("            if units == offset {")
+    appendLine("                return Some(index);")
+    appendLine("            }")
+    appendLine("            if units > offset {")
+    appendLine("                return None;")
+    appendLine("            }")
+    appendLine("            units += c.len_utf16();")
+    appendLine("        }")
+    appendLine("        (units == offset).then_some(text.len())")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// UTF-16 offset of the byte `index` in `text`, `None` past the end or inside a character.")
+    appendLine("    pub fn offset(text: &str, index: usize) -> Option<usize> {")
+    appendLine("        text.is_char_boundary(index).then(|| len(&text[..index]))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Byte range of the UTF-16 `offset` and `length` in `text`.")
+    appendLine("    pub fn byte_range(text: &str, offset: usize, length: usize) -> Option<std::ops::Range<usize>> {")
+    appendLine("        let start = byte_index(text, offset)?;")
+    appendLine("        let end = start + byte_index(&text[start..], length)?;")
+    appendLine("        Some(start..end)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Type of a [MessageEntity] with the fields used by that type.")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Debug)]")
+    appendLine("pub enum EntityKind<'a> {")
+    appendLine("    Mention,")
+    appendLine("    Hashtag,")
+    appendLine("    Cashtag,")
+    appendLine("    BotCommand,")
+    appendLine("    Url,")
+    appendLine("    Email,")
+    appendLine("    PhoneNumber,")
+    appendLine("    Bold,")
+    appendLine("    Italic,")
+    appendLine("    Underline,")
+    appendLine("    Strikethrough,")
+    appendLine("    Spoiler,")
+    appendLine("    Blockquote,")
+    appendLine("    ExpandableBlockquote,")
+    appendLine("    Code,")
+    appendLine("    Pre { language: Option<&'a str> },")
+    appendLine("    TextLink { url: &'a str },")
+    appendLine("    TextMention { user: &'a User },")
+    appendLine("    CustomEmoji { custom_emoji_id: &'a str },")
+    appendLine("    /// A type added after these models, or a text_link, text_mention or custom_emoji without its field.")
+    appendLine("    Unknown(&'a str),")
+    appendLine("}")
+    appendLine()
+    appendLine("impl MessageEntity {")
+    appendLine("    /// Type of the entity, with `url`, `user`, `language` and `custom_emoji_id` when they belong to it.")
+    appendLine("    pub fn kind(&self) -> EntityKind<'_> {")
+    appendLine("        match self.type_.as_str() {")
+    appendLine("            \"mention\" => EntityKind::Mention,")
+    appendLine("            \"hashtag\" => EntityKind::Hashtag,")
+    appendLine("            \"cashtag\" => EntityKind::Cashtag,")
+    appendLine("            \"bot_command\" => EntityKind::BotCommand,")
+    appendLine("            \"url\" => EntityKind::Url,")
+    appendLine("            \"email\" => EntityKind::Email,")
+    appendLine("            \"phone_number\" => EntityKind::PhoneNumber,")
+    appendLine("            \"bold\" => EntityKind::Bold,")
+    appendLine("            \"italic\" => EntityKind::Italic,")
+    appendLine("            \"underline\" => EntityKind::Underline,")
+    appendLine("            \"strikethrough\" => EntityKind::Strikethrough,")
+    appendLine("            \"spoiler\" => EntityKind::Spoiler,")
+    appendLine("            \"blockquote\" => EntityKind::Blockquote,")
+    appendLine("            \"expandable_blockquote\" => EntityKind::ExpandableBlockquote,")
+    appendLine("            \"code\" => EntityKind::Code,")
+    appendLine("            \"pre\" => EntityKind::Pre { language: self.language.as_deref() },")
+    appendLine("            \"text_link\" => match &self.url {")
+    appendLine("                Some(url) => EntityKind::TextLink { url },")
+    appendLine("                None => EntityKind::Unknown(&self.type_),")
+    appendLine("            },")
+    appendLine("            \"text_mention\" => match &self.user {")
+    appendLine("                Some(user) => EntityKind::TextMention { user },")
+    appendLine("                None => EntityKind::Unknown(&self.type_),")
+    appendLine("            },")
+    appendLine("            \"custom_emoji\" => match &self.custom_emoji_id {")
+    appendLine("                Some(custom_emoji_id) => EntityKind::CustomEmoji { custom_emoji_id },")
+    appendLine("                None => EntityKind::Unknown(&self.type_),")
+    appendLine("            },")
+    appendLine("            other => EntityKind::Unknown(other),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Byte range of the entity in `text`, `None` when it's outside `text` or splits a character.")
+    appendLine("    pub fn byte_range(&self, text: &str) -> Option<std::ops::Range<usize>> {")
+    appendLine("        utf16::byte_range(text, usize::try_from(self.offset).ok()?, usize::try_from(self.length).ok()?)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Part of `text` covered by the entity, `None` when it's outside `text` or splits a character.")
+    appendLine("    pub fn text_in<'a>(&self, text: &'a str) -> Option<&'a str> {")
+    appendLine("        self.byte_range(text).map(|range| &text[range])")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Link opened by the entity: the covered text of a `url`, the `url` field of a `text_link`.")
+    appendLine("    pub fn link<'a>(&'a self, text: &'a str) -> Option<&'a str> {")
+    appendLine("        match self.kind() {")
+    appendLine("            EntityKind::Url => self.text_in(text),")
+    appendLine("            EntityKind::TextLink { url } => Some(url),")
+    appendLine("            _ => None,")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// User mentioned by a `text_mention`.")
+    appendLine("    pub fn mentioned_user(&self) -> Option<&User> {")
+    appendLine("        match self.kind() {")
+    appendLine("            EntityKind::TextMention { user } => Some(user),")
+    appendLine("            _ => None,")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Sticker identifier of a `custom_emoji`.")
+    appendLine("    pub fn custom_emoji(&self) -> Option<&str> {")
+    appendLine("        match self.kind() {")
+    appendLine("            EntityKind::CustomEmoji { custom_emoji_id } => Some(custom_emoji_id),")
+    appendLine("            _ => None,")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/// Pairs every entity with the part of `text` it covers, skipping the entities outside `text`.")
+    appendLine("fn entities_with_text<'a>(text: Option<&'a str>, entities: Option<&'a [MessageEntity]>) -> impl Iterator<Item = (&'a MessageEntity, &'a str)> {")
+    appendLine("    let text = text.unwrap_or_default();")
+    appendLine("    entities.unwrap_or_default().iter().filter_map(move |entity| entity.text_in(text).map(|covered| (entity, covered)))")
+    appendLine("}")
+}
+
+private fun rustFormattedText() = buildString {
+    appendLine("/// Markup of a text with a `parse_mode`.")
+    appendLine("#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]")
+    appendLine("pub enum Markup {")
+    appendLine("    Html,")
+    appendLine("    MarkdownV2,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl Markup {")
+    appendLine("    /// Value of `parse_mode`, \"HTML\" or \"MarkdownV2\".")
+    appendLine("    pub fn name(self) -> &'static str {")
+    appendLine("        match self {")
+    appendLine("            Self::Html => \"HTML\",")
+    appendLine("            Self::MarkdownV2 => \"MarkdownV2\",")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// `parse_mode` of this markup, fails if [ParseMode] doesn't deserialize from [Markup::name].")
+    appendLine("    pub fn parse_mode(self) -> Result<ParseMode, serde_json::Error> {")
+    appendLine("        serde_json::from_value(serde_json::Value::from(self.name()))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Renders `text` with its `entities`, e.g. from [Message::text] and [Message::entities], see [FormattedText::to_markup].")
+    appendLine("    pub fn render(self, text: &str, entities: &[MessageEntity]) -> String {")
+    appendLine("        render_entities(text, entities, self)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Escapes `text` so that it's shown as is.")
+    appendLine("    pub fn escape(self, text: &str) -> String {")
+    appendLine("        let mut escaped = String::with_capacity(text.len());")
+    appendLine("        match self {")
+    appendLine("            Self::Html => escape_html(&mut escaped, text),")
+    appendLine("            Self::MarkdownV2 => escape_markdown_v2(&mut escaped, text, MARKDOWN_V2_SPECIAL),")
+    appendLine("        }")
+    appendLine("        escaped")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("// Characters escaped with '\\' in MarkdownV2, depending on where they are")
+    appendLine("const MARKDOWN_V2_SPECIAL: &str = \"_*[]()~`>#+-=|{}.!\\\\\";")
+    appendLine("const MARKDOWN_V2_CODE_SPECIAL: &str = \"`\\\\\";")
+    appendLine("const MARKDOWN_V2_LINK_SPECIAL: &str = \")\\\\\";")
+    appendLine()
+    appendLine("fn escape_markdown_v2(out: &mut String, text: &str, special: &str) {")
+    appendLine("    for c in text.chars() {")
+    appendLine("        if special.contains(c) {")
+    appendLine("            out.push('\\\\');")
+    appendLine("        }")
+    appendLine("        out.push(c);")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn escape_html(out: &mut String, text: &str) {")
+    appendLine("    for c in text.chars() {")
+    appendLine("        match c {")
+    appendLine("            '<' => out.push_str(\"&lt;\"),")
+    appendLine("            '>' => out.push_str(\"&gt;\"),")
+    appendLine("            '&' => out.push_str(\"&amp;\"),")
+    appendLine("            '\"' => out.push_str(\"&quot;\"),")
+    appendLine("            c => out.push(c),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Text with [MessageEntity] built piece by piece, the UTF-16 offsets are computed while appending.")
+    appendLine(" *")
+    appendLine(" * It's sent as text + entities (see [FormattedText::text] and [FormattedText::entities]), or as HTML or MarkdownV2")
+    appendLine(" * escaped by [FormattedText::to_markup].")
+    appendLine(" * */")
+    appendLine("#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]")
+    appendLine("pub struct FormattedText {")
+    appendLine("    text: String,")
+    appendLine("    entities: Vec<MessageEntity>,")
+    appendLine("    length: usize,")
+    appendLine("}")
+    appendLine()
+    appendLine("impl FormattedText {")
+    appendLine("    /// Creates an empty [FormattedText].")
+    appendLine("    pub fn new() -> Self {")
+    appendLine("        Self::default()")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Text without formatting.")
+    appendLine("    pub fn text(&self) -> &str {")
+    appendLine("        &self.text")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Entities of [FormattedText::text], ordered by offset.")
+    appendLine("    pub fn entities(&self) -> &[MessageEntity] {")
+    appendLine("        &self.entities")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Length of the text in UTF-16 code units, the unit of the Telegram limits.")
+    appendLine("    pub fn len_utf16(&self) -> usize {")
+    appendLine("        self.length")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Text and entities, for `text` + `entities` or `caption` + `caption_entities`.")
+    appendLine("    pub fn into_parts(self) -> (String, Vec<MessageEntity>) {")
+    appendLine("        (self.text, self.entities)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Text escaped for `markup`, to send with `parse_mode` [Markup::parse_mode].")
+    appendLine("    pub fn to_markup(&self, markup: Markup) -> String {")
+    appendLine("        render_entities(&self.text, &self.entities, markup)")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `text` without formatting.")
+    appendLine("    pub fn plain(mut self, text: &str) -> Self {")
+    appendLine("        self.text.push_str(text);")
+    appendLine("        self.length += utf16::len(text);")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` (text or [FormattedText]) inside the entity created from its UTF-16 offset and length.")
+    appendLine("    pub fn entity(mut self, content: impl Into<FormattedText>, entity: impl FnOnce(Integer, Integer) -> MessageEntity) -> Self {")
+    appendLine("        let content = content.into();")
+    appendLine("        let offset = self.length;")
+    appendLine("        self.entities.push(entity(to_integer(offset), to_integer(content.length)));")
+    appendLine("        self.entities.extend(content.entities.into_iter().map(|mut inner| {")
+    appendLine("            inner.offset += to_integer(offset);")
+    appendLine("            inner")
+    appendLine("        }));")
+    appendLine("        self.text.push_str(&content.text);")
+    appendLine("        self.length += content.length;")
+    appendLine("        self")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends **bold** `content`.")
+    appendLine("    pub fn bold(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"bold\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends _italic_ `content`.")
+    appendLine("    pub fn italic(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"italic\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends underlined `content`.")
+    appendLine("    pub fn underline(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"underline\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends ~~strikethrough~~ `content`.")
+    appendLine("    pub fn strikethrough(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"strikethrough\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` hidden by a spoiler.")
+    appendLine("    pub fn spoiler(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"spoiler\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `code` in a monowidth font.")
+    appendLine("    pub fn code(self, code: &str) -> Self {")
+    appendLine("        self.entity(code, |offset, length| MessageEntity::new(\"code\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends a monowidth block of `code`, highlighted as `language` when present.")
+    appendLine("    pub fn pre(self, code: &str, language: Option<&str>) -> Self {")
+    appendLine("        self.entity(code, |offset, length| {")
+    appendLine("            let entity = MessageEntity::new(\"pre\", offset, length);")
+    appendLine("            match language {")
+    appendLine("                Some(language) => entity.language(language),")
+    appendLine("                None => entity,")
+    appendLine("            }")
+    appendLine("        })")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` opening `url`.")
+    appendLine("    pub fn text_link(self, content: impl Into<FormattedText>, url: impl Into<String>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"text_link\", offset, length).url(url))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` mentioning `user`, also users without username.")
+    appendLine("    pub fn text_mention(self, content: impl Into<FormattedText>, user: User) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"text_mention\", offset, length).user(user))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends the custom emoji `custom_emoji_id`, `emoji` is shown where it's not supported.")
+    appendLine("    pub fn custom_emoji(self, emoji: &str, custom_emoji_id: impl Into<String>) -> Self {")
+    appendLine("        self.entity(emoji, |offset, length| MessageEntity::new(\"custom_emoji\", offset, length).custom_emoji_id(custom_emoji_id))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` as a block quotation, it must start a line.")
+    appendLine("    pub fn blockquote(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"blockquote\", offset, length))")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /// Appends `content` as a block quotation collapsed by default, it must start a line.")
+    appendLine("    pub fn expandable_blockquote(self, content: impl Into<FormattedText>) -> Self {")
+    appendLine("        self.entity(content, |offset, length| MessageEntity::new(\"expandable_blockquote\", offset, length))")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn to_integer(value: usize) -> Integer {")
+    appendLine("    Integer::try_from(value).expect(\"UTF-16 offset out of the Integer range\")")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<&str> for FormattedText {")
+    appendLine("    fn from(text: &str) -> Self {")
+    appendLine("        Self::new().plain(text)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("impl From<String> for FormattedText {")
+    appendLine("    fn from(text: String) -> Self {")
+    appendLine("        Self::new().plain(&text)")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("/**")
+    appendLine(" * Renders `text` with its entities in `markup`.")
+    appendLine(" *")
+    appendLine(" * Overlapping entities are closed and opened again, e.g. bold \"ab\" and italic \"bc\" in \"abc\" are `*a_b_*_c_`.")
+    appendLine(" * Quotations contain the other entities, the entities outside `text` are skipped.")
+    appendLine(" * */")
+    appendLine("fn render_entities(text: &str, entities: &[MessageEntity], markup: Markup) -> String {")
+    appendLine("    fn is_quote(entity: &MessageEntity) -> bool {")
+    appendLine("        matches!(entity.kind(), EntityKind::Blockquote | EntityKind::ExpandableBlockquote)")
+    appendLine("    }")
+    appendLine("    let mut spans: Vec<_> = entities")
+    appendLine("        .iter()")
+    appendLine("        .filter_map(|entity| entity.byte_range(text).filter(|range| !range.is_empty()).map(|range| (range, entity)))")
+    appendLine("        .collect();")
+    appendLine("    spans.sort_by_key(|(range, entity)| (range.start, !is_quote(entity), std::cmp::Reverse(range.end)));")
+    appendLine("    let mut out = String::with_capacity(text.len() * 2);")
+    appendLine("    // open entities with their end, the last one is the innermost")
+    appendLine("    let mut open: Vec<(usize, &MessageEntity)> = Vec::new();")
+    appendLine("    let mut spans = spans.into_iter().peekable();")
+    appendLine("    let mut position = 0;")
+    appendLine("    loop {")
+    appendLine("        if let Some(index) = open.iter().position(|(end, _)| *end == position) {")
+    appendLine("            // the entities opened after the ending ones are closed and opened again")
+    appendLine("            for (_, entity) in open[index..].iter().rev() {")
+    appendLine("                close_entity(&mut out, entity, markup);")
+    appendLine("            }")
+    appendLine("            let reopened: Vec<_> = open.drain(index..).filter(|(end, _)| *end != position).collect();")
+    appendLine("            for (_, entity) in &reopened {")
+    appendLine("                open_entity(&mut out, entity, markup);")
+    appendLine("            }")
+    appendLine("            open.extend(reopened);")
+    appendLine("        }")
+    appendLine("        while let Some((range, entity)) = spans.next_if(|(range, _)| range.start == position) {")
+    appendLine("            let index = if is_quote(entity) { open.iter().take_while(|(_, open)| is_quote(open)).count() } else { open.len() };")
+    appendLine("            // in MarkdownV2 the quotation marker starts the line, the closed entities end the previous one")
+    appendLine("            let newline = markup == Markup::MarkdownV2 && index < open.len() && out.ends_with('\\n');")
+    appendLine("            if newline {")
+    appendLine("                out.pop();")
+    appendLine("            }")
+    appendLine("            for (_, entity) in open[index..].iter().rev() {")
+    appendLine("                close_entity(&mut out, entity, markup);")
+    appendLine("            }")
+    appendLine("            if newline {")
+    appendLine("                out.push('\\n');")
+    appendLine("            }")
+    appendLine("            open_entity(&mut out, entity, markup);")
+    appendLine("            for (_, entity) in &open[index..] {")
+    appendLine("                open_entity(&mut out, entity, markup);")
+    appendLine("            }")
+    appendLine("            open.insert(index, (range.end, entity));")
+    appendLine("        }")
+    appendLine("        if position == text.len() {")
+    appendLine("            return out;")
+    appendLine("        }")
+    appendLine("        let stop = spans")
+    appendLine("            .peek()")
+    appendLine("            .map(|(range, _)| range.start)")
+    appendLine("            .into_iter()")
+    appendLine("            .chain(open.iter().map(|(end, _)| *end))")
+    appendLine("            .fold(text.len(), usize::min);")
+    appendLine("        write_text(&mut out, text, position..stop, &open, markup);")
+    appendLine("        position = stop;")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("// Writes text[range], in MarkdownV2 the lines of a quotation start with '>'")
+    appendLine("fn write_text(out: &mut String, text: &str, range: std::ops::Range<usize>, open: &[(usize, &MessageEntity)], markup: Markup) {")
+    appendLine("    match markup {")
+    appendLine("        Markup::Html => escape_html(out, &text[range]),")
+    appendLine("        Markup::MarkdownV2 => {")
+    appendLine("            let code = open.iter().any(|(_, entity)| matches!(entity.kind(), EntityKind::Code | EntityKind::Pre { .. }));")
+    appendLine("            let quote_end = open")
+    appendLine("                .iter()")
+    appendLine("                .find(|(_, entity)| matches!(entity.kind(), EntityKind::Blockquote | EntityKind::ExpandableBlockquote))")
+    appendLine("                .map(|(end, _)| *end);")
+    appendLine("            let mut position = range.start;")
+    appendLine("            for line in text[range].split_inclusive('\\n') {")
+    appendLine("                escape_markdown_v2(out, line, if code { MARKDOWN_V2_CODE_SPECIAL } else { MARKDOWN_V2_SPECIAL });")
+    appendLine("                position += line.len();")
+    appendLine("                if line.ends_with('\\n') && quote_end.is_some_and(|end| position < end) {")
+    appendLine("                    out.push('>');")
+    appendLine("                }")
+    appendLine("            }")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn open_entity(out: &mut String, entity: &MessageEntity, markup: Markup) {")
+    appendLine("    let kind = entity.kind();")
+    appendLine("    match markup {")
+    appendLine("        Markup::Html => match kind {")
+    appendLine("            EntityKind::Bold => out.push_str(\"<b>\"),")
+    appendLine("            EntityKind::Italic => out.push_str(\"<i>\"),")
+    appendLine("            EntityKind::Underline => out.push_str(\"<u>\"),")
+    appendLine("            EntityKind::Strikethrough => out.push_str(\"<s>\"),")
+    appendLine("            EntityKind::Spoiler => out.push_str(\"<tg-spoiler>\"),")
+    appendLine("            EntityKind::Code => out.push_str(\"<code>\"),")
+    appendLine("            EntityKind::Pre { language: None } => out.push_str(\"<pre>\"),")
+    appendLine("            EntityKind::Pre { language: Some(language) } => {")
+    appendLine("                out.push_str(\"<pre><code class=\\\"language-\");")
+    appendLine("                escape_html(out, language);")
+    appendLine("                out.push_str(\"\\\">\");")
+    appendLine("            }")
+    appendLine("            EntityKind::TextLink { url } => {")
+    appendLine("                out.push_str(\"<a href=\\\"\");")
+    appendLine("                escape_html(out, url);")
+    appendLine("                out.push_str(\"\\\">\");")
+    appendLine("            }")
+    appendLine("            EntityKind::TextMention { user } => out.push_str(&format!(\"<a href=\\\"tg://user?id={}\\\">\", user.id)),")
+    appendLine("            EntityKind::CustomEmoji { custom_emoji_id } => {")
+    appendLine("                out.push_str(\"<tg-emoji emoji-id=\\\"\");")
+    appendLine("                escape_html(out, custom_emoji_id);")
+    appendLine("                out.push_str(\"\\\">\");")
+    appendLine("            }")
+    appendLine("            EntityKind::Blockquote => out.push_str(\"<blockquote>\"),")
+    appendLine("            EntityKind::ExpandableBlockquote => out.push_str(\"<blockquote expandable>\"),")
+    appendLine("            _ => {}")
+    appendLine("        },")
+    appendLine("        Markup::MarkdownV2 => match kind {")
+    appendLine("            EntityKind::Pre { language } => {")
+    appendLine("                out.push_str(\"```\");")
+    appendLine("                escape_markdown_v2(out, language.unwrap_or_default(), MARKDOWN_V2_CODE_SPECIAL);")
+    appendLine("                out.push('\\n');")
+    appendLine("            }")
+    appendLine("            EntityKind::TextLink { .. } | EntityKind::TextMention { .. } => out.push('['),")
+    appendLine("            EntityKind::CustomEmoji { .. } => out.push_str(\"![\"),")
+    appendLine("            EntityKind::Blockquote => out.push('>'),")
+    appendLine("            EntityKind::ExpandableBlockquote => out.push_str(\"**>\"),")
+    appendLine("            kind => push_markdown_v2_marker(out, markdown_v2_marker(kind)),")
+    appendLine("        },")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("fn close_entity(out: &mut String, entity: &MessageEntity, markup: Markup) {")
+    appendLine("    let kind = entity.kind();")
+    appendLine("    match markup {")
+    appendLine("        Markup::Html => match kind {")
+    appendLine("            EntityKind::Bold => out.push_str(\"</b>\"),")
+    appendLine("            EntityKind::Italic => out.push_str(\"</i>\"),")
+    appendLine("            EntityKind::Underline => out.push_str(\"</u>\"),")
+    appendLine("            EntityKind::Strikethrough => out.push_str(\"</s>\"),")
+    appendLine("            EntityKind::Spoiler => out.push_str(\"</tg-spoiler>\"),")
+    appendLine("            EntityKind::Code => out.push_str(\"</code>\"),")
+    appendLine("            EntityKind::Pre { language: None } => out.push_str(\"</pre>\"),")
+    appendLine("            EntityKind::Pre { language: Some(_) } => out.push_str(\"</code></pre>\"),")
+    appendLine("            EntityKind::TextLink { .. } | EntityKind::TextMention { .. } => out.push_str(\"</a>\"),")
+    appendLine("            EntityKind::CustomEmoji { .. } => out.push_str(\"</tg-emoji>\"),")
+    appendLine("            EntityKind::Blockquote | EntityKind::ExpandableBlockquote => out.push_str(\"</blockquote>\"),")
+    appendLine("            _ => {}")
+    appendLine("        },")
+    appendLine("        Markup::MarkdownV2 => match kind {")
+    appendLine("            EntityKind::TextLink { url } => {")
+    appendLine("                out.push_str(\"](\");")
+    appendLine("                escape_markdown_v2(out, url, MARKDOWN_V2_LINK_SPECIAL);")
+    appendLine("                out.push(')');")
+    appendLine("            }")
+    appendLine("            EntityKind::TextMention { user } => out.push_str(&format!(\"](tg://user?id={})\", user.id)),")
+    appendLine("            EntityKind::CustomEmoji { custom_emoji_id } => {")
+    appendLine("                out.push_str(\"](tg://emoji?id=\");")
+    appendLine("                escape_markdown_v2(out, custom_emoji_id, MARKDOWN_V2_LINK_SPECIAL);")
+    appendLine("                out.push(')');")
+    appendLine("            }")
+    appendLine("            EntityKind::Pre { .. } => out.push_str(\"```\"),")
+    appendLine("            EntityKind::Blockquote => {}")
+    appendLine("            EntityKind::ExpandableBlockquote => out.push_str(\"||\"),")
+    appendLine("            kind => push_markdown_v2_marker(out, markdown_v2_marker(kind)),")
+    appendLine("        },")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("// Marker opening and closing the entities written as a pair of the same marker")
+    appendLine("fn markdown_v2_marker(kind: EntityKind) -> &'static str {")
+    appendLine("    match kind {")
+    appendLine("        EntityKind::Bold => \"*\",")
+    appendLine("        EntityKind::Italic => \"_\",")
+    appendLine("        EntityKind::Underline => \"__\",")
+    appendLine("        EntityKind::Strikethrough => \"~\",")
+    appendLine("        EntityKind::Spoiler => \"||\",")
+    appendLine("        EntityKind::Code => \"`\",")
+    appendLine("        _ => \"\",")
+    appendLine("    }")
+    appendLine("}")
+    appendLine()
+    appendLine("// \"___\" is ambiguous between italic and underline, \"\\r\" separates them as documented by Telegram")
+    appendLine("fn push_markdown_v2_marker(out: &mut String, marker: &str) {")
+    appendLine("    if marker.starts_with('_') && out.ends_with('_') && !out.ends_with(\"\\\\_\") {")
+    appendLine("        out.push('\\r');")
+    appendLine("    }")
+    appendLine("    out.push_str(marker);")
+    appendLine("}")
+}
+
+private fun rustSplitText() = buildString {
+    appendLine("impl FormattedText {")
+    appendLine("    /// Text and entities received in a message, e.g. [Message::text] and [Message::entities].")
+    appendLine("    pub fn from_parts(text: impl Into<String>, entities: impl Into<Vec<MessageEntity>>) -> Self {")
+    appendLine("        let text = text.into();")
+    appendLine("        let length = utf16::len(&text);")
+    appendLine("        Self { text, entities: entities.into(), length }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    /**")
+    appendLine("     * Splits the text in parts of at most `max_length` UTF-16 code units, e.g. [SendMessageRequest::TEXT_MAX_LENGTH].")
+    appendLine("     *")
+    appendLine("     * Parts end at the last paragraph, line or word that fits, the separator is dropped. A `pre` or `code` entity")
+    appendLine("     * crossing the end starts the next part when it fits in one. The entities are clipped to each part.")
+    appendLine("     * An empty text gives one empty part.")
+    appendLine("     * */")
+    appendLine("    pub fn split(&self, max_length: usize) -> Vec<FormattedText> {")
+    appendLine("        let spans: Vec<_> = self.entities.iter().filter_map(|entity| entity.byte_range(&self.text).map(|range| (range, entity))).collect();")
+    appendLine("        let mut parts = Vec::new();")
+    appendLine("        let mut start = 0;")
+    appendLine("        while start < self.text.len() {")
+    appendLine("            // at least one character, a longer one than max_length would never fit")
+    appendLine("            let limit = self.text[start..]")
+    appendLine("                .char_indices()")
+    appendLine("                .scan(0, |units, (index, c)| {")
+    appendLine("                    *units += c.len_utf16();")
+    appendLine("                    Some((start + index + c.len_utf8(), *units))")
+    appendLine("                })")
+    appendLine("                .enumerate()")
+    appendLine("                .take_while(|(count, (_, units))| *count == 0 || *units <= max_length)")
+    appendLine("                .last()")
+    appendLine("                .map_or(self.text.len(), |(_, (end, _))| end);")
+    appendLine("            let (end, next) = if limit == self.text.len() { (limit, limit) } else { self.split_point(start, limit, max_length, &spans) };")
+    appendLine("            if end > start {")
+    appendLine("                parts.push(self.slice(start..end, &spans));")
+    appendLine("            }")
+    appendLine("            start = next;")
+    appendLine("        }")
+    appendLine("        if parts.is_empty() {")
+    appendLine("            parts.push(FormattedText::new());")
+    appendLine("        }")
+    appendLine("        parts")
+    appendLine("    }")
+    appendLine()
+    appendLine("    // End of the part starting at `start`, and start of the next one")
+    appendLine("    fn split_point(&self, start: usize, limit: usize, max_length: usize, spans: &[(std::ops::Range<usize>, &MessageEntity)]) -> (usize, usize) {")
+    appendLine("        // code blocks fitting in a part are not split")
+    appendLine("        let inside_code = |index: usize| {")
+    appendLine("            spans.iter().find(|(range, entity)| {")
+    appendLine("                matches!(entity.kind(), EntityKind::Code | EntityKind::Pre { .. })")
+    appendLine("                    && range.start < index")
+    appendLine("                    && index < range.end")
+    appendLine("                    && utf16::len(&self.text[range.clone()]) <= max_length")
+    appendLine("            })")
+    appendLine("        };")
+    appendLine("        let window = &self.text[start..limit];")
+    appendLine("        let separator = [\"\\n\\n\", \"\\n\", \" \"].iter().find_map(|separator| {")
+    appendLine("            window")
+    appendLine("                .rmatch_indices(separator)")
+    appendLine("                .map(|(index, _)| start + index)")
+    appendLine("                .find(|index| *index > start && inside_code(*index).is_none())")
+    appendLine("                .map(|index| (index, index + separator.len()))")
+    appendLine("        });")
+    appendLine("        match (separator, inside_code(limit)) {")
+    appendLine("            (Some(split), _) => split,")
+    appendLine("            (None, Some((range, _))) if range.start > start => (range.start, range.start),")
+    appendLine("            (None, _) => (limit, limit),")
+    appendLine("        }")
+    appendLine("    }")
+    appendLine()
+    appendLine("    fn slice(&self, part: std::ops::Range<usize>, spans: &[(std::ops::Range<usize>, &MessageEntity)]) -> FormattedText {")
+    appendLine("        let entities: Vec<_> = spans")
+    appendLine("            .iter()")
+    appendLine("            .filter_map(|(range, entity)| {")
+    appendLine("                let start = range.start.max(part.start);")
+    appendLine("                let end = range.end.min(part.end);")
+    appendLine("                (start < end).then(|| {")
+    appendLine("                    let mut entity = (*entity).clone();")
+    appendLine("                    entity.offset = to_integer(utf16::len(&self.text[part.start..start]));")
+    appendLine("                    entity.length = to_integer(utf16::len(&self.text[start..end]));")
+    appendLine("                    entity")
+    appendLine("                })")
+    appendLine("            })")
+    appendLine("            .collect();")
+    appendLine("        FormattedText::from_parts(&self.text[part], entities)")
+    appendLine("    }")
+    appendLine("}")
+}
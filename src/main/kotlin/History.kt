@@ -0,0 +1,32 @@
+import com.google.gson.GsonBuilder
+import com.google.gson.JsonParser
+
+private val gson = GsonBuilder().setPrettyPrinting().disableHtmlEscaping().create()
+
+/**
+ * Names of every type, field, method and parameter, e.g. "Message", "Message.date", "sendMessage", "sendMessage.chat_id".
+ */
+fun List<DocSection>.toItemNames(): Set<String> = flatMap { section ->
+    section.docTypes.flatMap { type -> listOf(type.name) + type.docFields.map { "${type.name}.${it.name}" } } +
+        section.docMethods.flatMap { method -> listOf(method.name) + method.docParameters.map { "${method.name}.${it.name}" } }
+}.toSet()
+
+/**
+ * Bot API version introducing each item of [toItemNames], read from a since.json, e.g. `{"Message.story": "7.8"}`.
+ * It's committed as example/since.json so that the generation doesn't depend on the git history.
+ */
+fun String.toSince(): Map<String, String> =
+    JsonParser.parseString(this).asJsonObject.entrySet().associate { (name, version) -> name to version.asString }
+
+fun Map<String, String>.toSinceJson(): String = gson.toJson(toSortedMap())
+
+/**
+ * [since] updated from [previous] to this snapshot: the new items are introduced by [version], the removed ones are dropped.
+ *
+ * Items of the first snapshot have no version, the docs don't tell when they appeared.
+ */
+fun List<DocSection>.withSince(since: Map<String, String>, previous: List<DocSection>, version: String): Map<String, String> {
+    val names = toItemNames()
+    val added = names - previous.toItemNames()
+    return (since + added.associateWith { version }).filterKeys { it in names }
+}
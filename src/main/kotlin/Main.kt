@@ -5,8 +5,47 @@ import io.ktor.client.statement.*
 import kotlinx.coroutines.runBlocking
 import org.jsoup.Jsoup
 import java.io.File
+import kotlin.system.exitProcess
+
+fun main(arguments: Array<String>) = runBlocking {
+    // Example: --derive=schemars::JsonSchema, added to every Rust model
+    val (options, args) = arguments.partition { it.startsWith("--") }
+    val extraDerives = options.filter { it.startsWith("--derive=") }.map { it.removePrefix("--derive=") }
+    val roundTripTests = "--round-trip-tests" in options
+    // Example: --since=example/since.json, the versions documented as "Since Bot API …"
+    val sinceFile = options.firstOrNull { it.startsWith("--since=") }?.removePrefix("--since=")
+    if (args.firstOrNull() == "diff") {
+        require(args.size in 3..4) { "Usage: diff <old telegram.json> <new telegram.json> [output directory]" }
+        val (old, new) = args.drop(1).take(2).map { File(it) }
+        val output = File(args.getOrElse(3) { "." }).apply { mkdirs() }
+        val changes = old.readText().toDocSections().diff(new.readText().toDocSections())
+        File(output, "changelog.md").writeText(changes.toChangelogMarkdown(old.path, new.path))
+        File(output, "changelog.json").writeText(changes.toChangelogJson(old.path, new.path))
+        println("📝 ${changes.size} changes, ${changes.count { it.breaking }} breaking - ${output.path}/changelog.md")
+        return@runBlocking
+    }
+    if (args.firstOrNull() == "offline") {
+        require(args.size in 2..4) {
+            "Usage: offline <telegramapi.html or telegram.json> [output directory] [Bot API version] [--derive=<path>...] [--round-trip-tests] [--since=<since.json>]"
+        }
+        val input = File(args[1])
+        val output = File(args.getOrElse(2) { "example" })
+        val text = input.readText()
+        val docs = try {
+            if (input.extension == "json") text.toDocSections() else Jsoup.parse(text).toSection()
+        } catch (e: Exception) {
+            System.err.println("❌ Can't parse ${input.path}: ${e.message}")
+            exitProcess(1)
+        }
+        val version = args.getOrNull(3)
+            ?: if (input.extension == "json") null else "Bot API ([\\d.]+)".toRegex().find(text)?.groupValues?.get(1)
+        println("👓 Bot API $version - Parse completed")
+        val since = sinceFile?.let { File(it).readText().toSince() }.orEmpty()
+        writeRustModels(docs, version, output, since, extraDerives, roundTripTests)
+        println("🎉 Bot API $version - ${output.path}/TelegramModels.rs generated!")
+        return@runBlocking
+    }
 
-fun main() = runBlocking {
     println("🏁 Start")
 
     val docs = Jsoup.parse(
@@ -14,25 +53,21 @@ fun main() = runBlocking {
             .also { File("data/telegramapi.html").writeText(it) }
     ).toSection()
 
-    val docsRequiredFirst = docs.map { doc ->
-        doc.copy(
-            docTypes = doc.docTypes.map { type ->
-                type.copy(
-                    docFields = type.docFields.sortedByDescending { it.required }
-                )
-            }.filter { it.name != "MessageId" },
-            docMethods = doc.docMethods.map { method ->
-                method.copy(
-                    docParameters = method.docParameters.sortedByDescending { it.required }
-                )
-            }
-        )
-    }
+    // MessageId is replaced by a value class in Kotlin
+    val kotlinDocs = docs.withRequiredFirst().map { doc -> doc.copy(docTypes = doc.docTypes.filter { it.name != "MessageId" }) }
 
     val version = "Bot API ([\\d.]+)".toRegex().find(File("data/telegramapi.html").readText())?.value
 
     println("👓 $version - Parse completed")
 
+    val previousVersion = "Last update: Telegram (Bot API \\S+)".toRegex().find(File("README.md").readText())?.groupValues?.get(1)
+    val previousDocs = File("example/telegram.json").takeIf { it.exists() }?.readText()?.toDocSections()
+    if (previousDocs != null) {
+        val changes = previousDocs.diff(docs)
+        File("example/changelog.md").writeText(changes.toChangelogMarkdown(previousVersion ?: "previous", version ?: "current"))
+        File("example/changelog.json").writeText(changes.toChangelogJson(previousVersion ?: "previous", version ?: "current"))
+    }
+
     if (version != null) {
         File("README.md").also { readme ->
             readme.writeText(
@@ -47,10 +82,55 @@ fun main() = runBlocking {
     File("example/telegram_tiny.md").writeText(docs.toReadmeTinyExample())
     File("example/telegram_full.md").writeText(docs.toReadmeFullExample())
     File("example/telegram.json").writeText(docs.toJson())
-    File("example/TelegramModelsOnly.kt").writeText(docsRequiredFirst.toKotlinModels(useKotlinXSerialization = false))
-    File("example/TelegramModels.kt").writeText(docsRequiredFirst.toKotlinModels(useKotlinXSerialization = true))
-    File("example/TelegramClient.kt").writeText(docsRequiredFirst.toKotlinMethods())
-    File("example/TelegramModels.rs").writeText(docs.toRustModels())
+    File("example/TelegramModelsOnly.kt").writeText(kotlinDocs.toKotlinModels(useKotlinXSerialization = false))
+    File("example/TelegramModels.kt").writeText(kotlinDocs.toKotlinModels(useKotlinXSerialization = true))
+    File("example/TelegramClient.kt").writeText(kotlinDocs.toKotlinMethods())
+    val rustVersion = version?.removePrefix("Bot API ")
+    val previousSince = File(sinceFile ?: "example/since.json").takeIf { it.exists() }?.readText()?.toSince().orEmpty()
+    val since = if (previousDocs != null && rustVersion != null) docs.withSince(previousSince, previousDocs, rustVersion) else previousSince
+    File("example/since.json").writeText(since.toSinceJson())
+    writeRustModels(docs, rustVersion, File("example"), since, extraDerives, roundTripTests)
 
     println("🎉 $version - Examples generated!")
+}
+
+/**
+ * Writes TelegramModels.rs in [output], with tests/fixtures and tests/round_trip.rs when [roundTripTests] is set.
+ *
+ * @param version e.g. "7.8"
+ * @param since Bot API version introducing each item, see [toSince]
+ * @param extraDerives derives added to every model, e.g. `schemars::JsonSchema`
+ * @param roundTripTests TelegramModels.rs then declares the tests module, it needs the tests directory next to it
+ */
+fun writeRustModels(
+    docs: List<DocSection>,
+    version: String?,
+    output: File,
+    since: Map<String, String> = emptyMap(),
+    extraDerives: List<String> = emptyList(),
+    roundTripTests: Boolean = false
+) {
+    val docsRequiredFirst = docs.withRequiredFirst()
+    output.mkdirs()
+    File(output, "TelegramModels.rs").writeText(docsRequiredFirst.toRustModels(extraDerives, version, since, roundTripTests))
+    if (roundTripTests) {
+        File(output, "tests/fixtures").apply { deleteRecursively() }.mkdirs()
+        docsRequiredFirst.toRustFixtures().forEach { (name, json) -> File(output, "tests/fixtures/$name.json").writeText(json) }
+        File(output, "tests/round_trip.rs").writeText(docsRequiredFirst.toRustRoundTripTests())
+    }
+}
+
+private fun List<DocSection>.withRequiredFirst() = map { doc ->
+    doc.copy(
+        docTypes = doc.docTypes.map { type ->
+            type.copy(
+                docFields = type.docFields.sortedByDescending { it.required }
+            )
+        },
+        docMethods = doc.docMethods.map { method ->
+            method.copy(
+                docParameters = method.docParameters.sortedByDescending { it.required }
+            )
+        }
+    )
 }
\ No newline at end of file
@@ -40,7 +40,8 @@ fun Document.toSection(): List<DocSection> {
         "((?:Array of )?\\S+) (?:objects? )?is returned".toRegex(),
         ".*[Rr]eturns .*?((?:Array of )?[A-Z]\\w+)".toRegex()
     )
-    val content = select("#dev_page_content").first()!!
+    val content = select("#dev_page_content").first()
+        ?: error("#dev_page_content not found, expected the page https://core.telegram.org/bots/api")
     var splitBy = ""
     return content.children()
         .groupBy {
@@ -82,6 +83,7 @@ fun Document.toSection(): List<DocSection> {
                                     // Field Type Description
                                     docFields = tableData.children().map { tableElement ->
                                         tableElement.getElementsByTag("td").let { tdElements ->
+                                            if (tdElements.size < 3) error("$h4: expected 3 columns in \"$tableHead\", found ${tdElements.size}")
                                             val fieldDesc = tdElements[2].html()
                                             val name = tdElements[0].text()
                                             var type = tdElements[1].text().fixTypeString()
@@ -101,6 +103,7 @@ fun Document.toSection(): List<DocSection> {
                                     // Parameter Type Required Description
                                     docParameters = tableData.children().map { tableElement ->
                                         tableElement.getElementsByTag("td").let { tdElement ->
+                                            if (tdElement.size < 4) error("$h4: expected 4 columns in \"$tableHead\", found ${tdElement.size}")
                                             val fieldDesc = tdElement[3].html()
                                             val name = tdElement[0].text()
                                             var type = tdElement[1].text().fixTypeString()
@@ -0,0 +1,19 @@
+import java.io.File
+import kotlin.test.Test
+import kotlin.test.assertEquals
+
+class GeneratorDiffTest {
+    private val docs = File("example/telegram.json").readText().toDocSections()
+
+    private fun without(type: String) = docs.map { section -> section.copy(docTypes = section.docTypes.filter { it.name != type }) }
+
+    // The variants are the ones of the generated enums, ChatMemberUpdated is named like one but has no status tag
+    @Test
+    fun variantsFollowTheGeneratedEnums() {
+        val banned = docs.diff(without("ChatMemberBanned")).filter { it.item == SchemaItem.Variant }
+        assertEquals(listOf("ChatMember.ChatMemberBanned"), banned.map { it.name })
+        assertEquals(true, banned.single().breaking)
+        val updated = docs.diff(without("ChatMemberUpdated")).filter { it.item == SchemaItem.Variant }
+        assertEquals(emptyList(), updated)
+    }
+}
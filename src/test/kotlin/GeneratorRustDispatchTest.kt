@@ -0,0 +1,18 @@
+import java.io.File
+import kotlin.test.Test
+import kotlin.test.assertEquals
+import kotlin.test.assertNotNull
+
+class GeneratorRustDispatchTest {
+    private val rust = File("example/telegram.json").readText().toDocSections().toRustModels()
+
+    // Dispatcher::poll sets the timeout of a GetUpdatesRequest, its value must have the unit type of the field
+    @Test
+    fun pollTimeoutHasTheTypeOfTheRequestField() {
+        val request = rust.substringAfter("pub struct GetUpdatesRequest {").substringBefore("\n}")
+        val field = assertNotNull("pub timeout: Option<(.+)>,".toRegex().find(request), "GetUpdatesRequest.timeout").groupValues[1]
+        val timeout = assertNotNull("request\\.timeout = request\\.timeout\\.or\\(Some\\((.+)\\)\\);".toRegex().find(rust), "poll timeout").groupValues[1]
+        assertEquals("std::time::Duration", field)
+        assertEquals("$field::from_secs($rustPollTimeoutSeconds)", timeout)
+    }
+}